@@ -0,0 +1,20 @@
+#[cfg(feature = "gl_struct")]
+extern crate gl_generator;
+
+fn main() {
+    #[cfg(feature = "gl_struct")]
+    {
+        use gl_generator::{Api, Fallbacks, Profile, Registry, StructGenerator};
+        use std::env;
+        use std::fs::File;
+        use std::path::Path;
+
+        let out_dir = env::var("OUT_DIR").unwrap();
+        let mut file = File::create(Path::new(&out_dir).join("gl_struct.rs")).unwrap();
+
+        // Uses the same API version as the `gl` crate.
+        Registry::new(Api::Gl, (4, 5), Profile::Core, Fallbacks::All, [])
+            .write_bindings(StructGenerator, &mut file)
+            .unwrap();
+    }
+}
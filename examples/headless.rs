@@ -0,0 +1,12 @@
+extern crate glutin_window;
+extern crate window;
+
+use glutin_window::HeadlessWindow;
+use window::WindowSettings;
+
+fn main() {
+    let window = HeadlessWindow::new(
+        &WindowSettings::new("Glutin Window", (640, 480))
+    ).unwrap();
+    println!("{:?}", window.context_info());
+}
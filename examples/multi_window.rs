@@ -0,0 +1,21 @@
+extern crate glutin_window;
+extern crate window;
+
+use glutin_window::GlutinEventLoop;
+use window::{Window, WindowSettings};
+
+fn main() {
+    let event_loop = GlutinEventLoop::new().unwrap();
+    let mut windows = vec![
+        event_loop.create_window(&WindowSettings::new("Main", (640, 480))).unwrap(),
+        event_loop.create_window(&WindowSettings::new("Tools", (320, 480))).unwrap(),
+    ];
+    while !windows.is_empty() {
+        for (i, window) in windows.iter_mut().enumerate() {
+            if let Some(e) = window.wait_event_timeout(std::time::Duration::from_millis(10)) {
+                println!("window {}: {:?}", i, e);
+            }
+        }
+        windows.retain(|window| !window.should_close());
+    }
+}
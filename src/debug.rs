@@ -0,0 +1,260 @@
+//! OpenGL debug output using `KHR_debug`.
+
+use input::Event;
+use input::event_id::EventId;
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// The event id of OpenGL debug messages.
+///
+/// The event is `Event::Custom` and the payload is [`DebugMessage`].
+pub const DEBUG_MESSAGE: EventId = EventId("glutin_window/debug_message");
+
+/// The source of an OpenGL debug message.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DebugSource {
+    /// Calls to the OpenGL API.
+    Api,
+    /// Calls to the window-system API.
+    WindowSystem,
+    /// The shader compiler.
+    ShaderCompiler,
+    /// A third party application associated with OpenGL.
+    ThirdParty,
+    /// The application itself.
+    Application,
+    /// Some other source.
+    Other,
+}
+
+/// The type of an OpenGL debug message.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DebugType {
+    /// An error, typically from the API.
+    Error,
+    /// Use of some behavior marked deprecated.
+    DeprecatedBehavior,
+    /// Something that invokes undefined behavior.
+    UndefinedBehavior,
+    /// Some functionality the user relies upon is not portable.
+    Portability,
+    /// Code that triggers possible performance issues.
+    Performance,
+    /// Command stream annotation.
+    Marker,
+    /// Entering a debug group.
+    PushGroup,
+    /// Leaving a debug group.
+    PopGroup,
+    /// Some other type.
+    Other,
+}
+
+/// The severity of an OpenGL debug message.
+///
+/// Severities are ordered from least to most severe.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum DebugSeverity {
+    /// Anything that is not an error or performance issue.
+    Notification,
+    /// Redundant state change or unimportant undefined behavior.
+    Low,
+    /// Major performance warnings, shader compilation warnings or use of deprecated behavior.
+    Medium,
+    /// Errors, undefined behavior or shader compilation errors.
+    High,
+}
+
+/// An OpenGL debug message.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DebugMessage {
+    /// The source of the message.
+    pub source: DebugSource,
+    /// The type of the message.
+    pub kind: DebugType,
+    /// The severity of the message.
+    pub severity: DebugSeverity,
+    /// The id of the message.
+    pub id: u32,
+    /// The message text.
+    pub message: String,
+}
+
+/// Where OpenGL debug messages are sent.
+pub enum DebugOutput {
+    /// Calls a user callback.
+    ///
+    /// The callback is called while OpenGL commands are executing,
+    /// so it should not call OpenGL itself.
+    Callback(Box<dyn FnMut(&DebugMessage) + Send>),
+    /// Logs messages using the `log` crate.
+    ///
+    /// High severity is logged as errors, medium as warnings,
+    /// low as info and notifications as debug.
+    #[cfg(feature = "log")]
+    Log,
+    /// Queues messages on the window event queue as `Event::Custom`.
+    ///
+    /// See [`DEBUG_MESSAGE`].
+    Event,
+}
+
+/// Settings for OpenGL debug output.
+///
+/// Debug output is only installed when the context is a debug context
+/// and the driver supports `glDebugMessageCallback`.
+pub struct DebugSettings {
+    /// Where messages are sent.
+    pub output: DebugOutput,
+    /// Messages with lower severity are ignored.
+    pub min_severity: DebugSeverity,
+    /// Messages with these ids are ignored.
+    pub blocked_ids: Vec<u32>,
+}
+
+impl DebugSettings {
+    /// Creates new debug settings that pass all messages to the output.
+    pub fn new(output: DebugOutput) -> DebugSettings {
+        DebugSettings {
+            output,
+            min_severity: DebugSeverity::Notification,
+            blocked_ids: vec![],
+        }
+    }
+
+    /// Sets the minimum severity of messages.
+    pub fn min_severity(mut self, value: DebugSeverity) -> Self {
+        self.min_severity = value;
+        self
+    }
+
+    /// Sets the ids of messages to ignore.
+    pub fn blocked_ids(mut self, value: Vec<u32>) -> Self {
+        self.blocked_ids = value;
+        self
+    }
+}
+
+// The state shared with the OpenGL debug callback.
+pub(crate) struct DebugState {
+    min_severity: DebugSeverity,
+    blocked_ids: Vec<u32>,
+    output: Mutex<DebugOutput>,
+    // Messages waiting to be added to the window event queue.
+    events: Mutex<VecDeque<DebugMessage>>,
+}
+
+impl DebugState {
+    // Installs the debug callback on the current OpenGL context.
+    //
+    // Returns `None` when `glDebugMessageCallback` is not available.
+    // The returned state must be kept alive while the context is in use.
+    pub(crate) fn install(settings: DebugSettings) -> Option<Box<DebugState>> {
+        if !gl::DebugMessageCallback::is_loaded() {return None}
+
+        let state = Box::new(DebugState {
+            min_severity: settings.min_severity,
+            blocked_ids: settings.blocked_ids,
+            output: Mutex::new(settings.output),
+            events: Mutex::new(VecDeque::new()),
+        });
+        unsafe {
+            gl::Enable(gl::DEBUG_OUTPUT);
+            // Makes the callback run on the thread of the OpenGL context.
+            gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+            gl::DebugMessageCallback(
+                Some(debug_callback),
+                &*state as *const DebugState as *const _,
+            );
+        }
+        Some(state)
+    }
+
+    // Moves queued messages to the window event queue.
+    pub(crate) fn drain_events(&self, events: &mut VecDeque<Event>) {
+        let mut queue = self.events.lock().unwrap_or_else(|err| err.into_inner());
+        for msg in queue.drain(..) {
+            events.push_back(Event::Custom(DEBUG_MESSAGE, Arc::new(msg), None));
+        }
+    }
+
+    fn handle(&self, msg: DebugMessage) {
+        if msg.severity < self.min_severity || self.blocked_ids.contains(&msg.id) {return}
+
+        let mut output = self.output.lock().unwrap_or_else(|err| err.into_inner());
+        match *output {
+            DebugOutput::Callback(ref mut cb) => cb(&msg),
+            #[cfg(feature = "log")]
+            DebugOutput::Log => {
+                let level = match msg.severity {
+                    DebugSeverity::High => log::Level::Error,
+                    DebugSeverity::Medium => log::Level::Warn,
+                    DebugSeverity::Low => log::Level::Info,
+                    DebugSeverity::Notification => log::Level::Debug,
+                };
+                log::log!(level, "OpenGL {:?} {:?} {}: {}",
+                    msg.source, msg.kind, msg.id, msg.message);
+            }
+            DebugOutput::Event => {
+                self.events.lock().unwrap_or_else(|err| err.into_inner()).push_back(msg);
+            }
+        }
+    }
+}
+
+extern "system" fn debug_callback(
+    source: gl::types::GLenum,
+    kind: gl::types::GLenum,
+    id: gl::types::GLuint,
+    severity: gl::types::GLenum,
+    length: gl::types::GLsizei,
+    message: *const gl::types::GLchar,
+    user_param: *mut std::os::raw::c_void,
+) {
+    if user_param.is_null() || message.is_null() {return}
+
+    let source = match source {
+        gl::DEBUG_SOURCE_API => DebugSource::Api,
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => DebugSource::WindowSystem,
+        gl::DEBUG_SOURCE_SHADER_COMPILER => DebugSource::ShaderCompiler,
+        gl::DEBUG_SOURCE_THIRD_PARTY => DebugSource::ThirdParty,
+        gl::DEBUG_SOURCE_APPLICATION => DebugSource::Application,
+        _ => DebugSource::Other,
+    };
+    let kind = match kind {
+        gl::DEBUG_TYPE_ERROR => DebugType::Error,
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => DebugType::DeprecatedBehavior,
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => DebugType::UndefinedBehavior,
+        gl::DEBUG_TYPE_PORTABILITY => DebugType::Portability,
+        gl::DEBUG_TYPE_PERFORMANCE => DebugType::Performance,
+        gl::DEBUG_TYPE_MARKER => DebugType::Marker,
+        gl::DEBUG_TYPE_PUSH_GROUP => DebugType::PushGroup,
+        gl::DEBUG_TYPE_POP_GROUP => DebugType::PopGroup,
+        _ => DebugType::Other,
+    };
+    let severity = match severity {
+        gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+        gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+        gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+        _ => DebugSeverity::Notification,
+    };
+    let message = unsafe {
+        if length >= 0 {
+            let bytes = std::slice::from_raw_parts(message as *const u8, length as usize);
+            String::from_utf8_lossy(bytes).into_owned()
+        } else {
+            std::ffi::CStr::from_ptr(message).to_string_lossy().into_owned()
+        }
+    };
+
+    let state = unsafe {&*(user_param as *const DebugState)};
+    // Unwinding into the OpenGL driver is undefined behavior, so the panic is dropped.
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        state.handle(DebugMessage {source, kind, severity, id, message})
+    }));
+    if res.is_err() {
+        #[cfg(feature = "log")]
+        log::error!("Panic in OpenGL debug callback");
+    }
+}
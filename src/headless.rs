@@ -0,0 +1,255 @@
+//! Headless windows rendering through EGL without a display server.
+
+use glutin::config::ConfigSurfaceTypes;
+use glutin::context::{NotCurrentGlContext, PossiblyCurrentGlContext};
+use glutin::display::GlDisplay;
+use glutin::prelude::GlSurface;
+use input::Event;
+use window::{
+    BuildFromWindowSettings,
+    OpenGLWindow,
+    ProcAddress,
+    Size,
+    Window,
+    WindowSettings,
+};
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use super::{
+    config_template_builder_from_settings,
+    create_context,
+    debug,
+    graphics_api_from_settings,
+    loader,
+    ContextFlags,
+    ContextInfo,
+    GlLoader,
+    GlutinWindowBuilder,
+    GlutinWindowError,
+    SharedContext,
+};
+
+/// A window without a display server, rendering through EGL.
+///
+/// The OpenGL context renders to a pbuffer of the window size.
+/// When pbuffers are not supported, the context is made current without a surface,
+/// in which case the application must render to its own framebuffer objects.
+///
+/// The window has a fixed size, never receives input events
+/// and swapping buffers waits for rendering to finish.
+/// This is useful for rendering tests on machines without a display,
+/// e.g. using the Mesa software renderer.
+pub struct HeadlessWindow {
+    /// The OpenGL context.
+    pub ctx: glutin::context::PossiblyCurrentContext,
+    /// The pbuffer surface, or `None` when the context is surfaceless.
+    pub surface: Option<glutin::surface::Surface<glutin::surface::PbufferSurface>>,
+    /// The EGL display.
+    pub display: glutin::display::Display,
+    size: Size,
+    should_close: bool,
+    context_info: ContextInfo,
+    context_flags: ContextFlags,
+    // Used for OpenGL debug messages.
+    events: VecDeque<Event>,
+    // Must be kept alive while the context is in use.
+    debug_state: Option<Box<debug::DebugState>>,
+}
+
+impl HeadlessWindow {
+    /// Creates a new headless window.
+    ///
+    /// Use [`GlutinWindowBuilder::build_headless`] for options that are specific to this back-end.
+    pub fn new(settings: &WindowSettings) -> Result<HeadlessWindow, GlutinWindowError> {
+        GlutinWindowBuilder::new(settings).build_headless()
+    }
+
+    /// Returns information about the created OpenGL context and config.
+    pub fn context_info(&self) -> &ContextInfo {
+        &self.context_info
+    }
+
+    /// Returns a loader of OpenGL functions for the context of this window.
+    pub fn loader(&self) -> GlLoader {
+        GlLoader::new(self.display.clone())
+    }
+
+    /// Creates an OpenGL context that shares objects with the context of this window.
+    ///
+    /// See [`crate::GlutinWindow::create_shared_context`].
+    pub fn create_shared_context(&self) -> Result<SharedContext, GlutinWindowError> {
+        SharedContext::new(&self.ctx, &self.context_flags)
+    }
+}
+
+impl GlutinWindowBuilder {
+    /// Builds a headless window using the first available EGL device.
+    ///
+    /// This does not require a display server.
+    pub fn build_headless(mut self) -> Result<HeadlessWindow, GlutinWindowError> {
+        use glutin::api::egl::device::Device;
+        use glutin::api::egl::display::Display as EglDisplay;
+        use glutin::context::PossiblyCurrentContext;
+        use glutin::context::NotCurrentContext;
+        use glutin::context::GlContext;
+        use glutin::surface::SurfaceAttributesBuilder;
+
+        let settings = &self.settings;
+        let config_picker = &mut self.config_picker;
+        let requirements = self.config_requirements;
+        let api = graphics_api_from_settings(settings)
+            .map_err(GlutinWindowError::UnsupportedApi)?;
+
+        let device = Device::query_devices()
+            .map_err(GlutinWindowError::DisplayCreation)?
+            .next()
+            .ok_or_else(|| GlutinWindowError::DisplayCreation(
+                glutin::error::ErrorKind::NotFound.into()
+            ))?;
+        let display = unsafe {EglDisplay::with_device(&device, None)}
+            .map_err(GlutinWindowError::DisplayCreation)?;
+        let display = glutin::display::Display::Egl(display);
+
+        // Prefer configs supporting pbuffers, and fall back to surfaceless.
+        let mut pick = |surface_types| {
+            let template = config_template_builder_from_settings(settings, &requirements, api)
+                .with_transparency(false)
+                .with_surface_type(surface_types)
+                .build();
+            let configs = unsafe {display.find_configs(template)}.ok()?;
+            let mut configs = configs.filter(|config| requirements.is_satisfied_by(config));
+            config_picker.pick(settings, &mut configs)
+        };
+        let (gl_config, pbuffer) = match pick(ConfigSurfaceTypes::PBUFFER) {
+            Some(gl_config) => (gl_config, true),
+            None => match pick(ConfigSurfaceTypes::empty()) {
+                Some(gl_config) => (gl_config, false),
+                None => return Err(GlutinWindowError::NoMatchingConfig(
+                    format!("No config satisfies the requirements ({})", requirements).into()
+                )),
+            }
+        };
+
+        let not_current_gl_context = create_context(
+            &display,
+            &gl_config,
+            None,
+            std::iter::once(api).chain(self.context_fallbacks.iter().cloned()),
+            &self.context_flags,
+            None,
+        )?;
+
+        let size = settings.get_size();
+        let (ctx, surface) = if pbuffer {
+            let w = NonZeroU32::new(size.width as u32).unwrap_or(NonZeroU32::MIN);
+            let h = NonZeroU32::new(size.height as u32).unwrap_or(NonZeroU32::MIN);
+            let surface_attributes = SurfaceAttributesBuilder::<glutin::surface::PbufferSurface>::new()
+                .with_srgb(Some(settings.get_srgb()))
+                .build(w, h);
+            let surface = unsafe {display.create_pbuffer_surface(&gl_config, &surface_attributes)}
+                .map_err(GlutinWindowError::SurfaceCreation)?;
+            let ctx = not_current_gl_context.make_current(&surface)
+                .map_err(GlutinWindowError::MakeCurrent)?;
+            (ctx, Some(surface))
+        } else {
+            #[allow(unreachable_patterns)]
+            let ctx = match not_current_gl_context {
+                NotCurrentContext::Egl(ctx) => ctx.make_current_surfaceless()
+                    .map_err(GlutinWindowError::MakeCurrent)?,
+                _ => unreachable!("The context was created with an EGL display"),
+            };
+            (PossiblyCurrentContext::Egl(ctx), None)
+        };
+
+        // Load the OpenGL function pointers.
+        GlLoader::new(display.clone()).load_global();
+
+        let context_info = ContextInfo::new(ctx.context_api(), &gl_config);
+        let debug_state = if self.context_flags.debug {
+            self.debug_settings.take().and_then(debug::DebugState::install)
+        } else {None};
+
+        Ok(HeadlessWindow {
+            ctx,
+            surface,
+            display,
+            size,
+            should_close: false,
+            context_info,
+            context_flags: self.context_flags,
+            events: VecDeque::new(),
+            debug_state,
+        })
+    }
+}
+
+impl Window for HeadlessWindow {
+    fn size(&self) -> Size {self.size}
+
+    fn draw_size(&self) -> Size {self.size}
+
+    fn should_close(&self) -> bool {self.should_close}
+
+    fn set_should_close(&mut self, value: bool) {self.should_close = value}
+
+    fn swap_buffers(&mut self) {
+        // There is nothing to present, so wait for rendering to finish instead.
+        unsafe {gl::Finish()};
+        if let Some(surface) = &self.surface {
+            let _ = surface.swap_buffers(&self.ctx);
+        }
+    }
+
+    fn wait_event(&mut self) -> Event {
+        use input::{IdleArgs, Loop};
+
+        self.poll_event().unwrap_or(Event::Loop(Loop::Idle(IdleArgs {dt: 0.0})))
+    }
+
+    fn wait_event_timeout(&mut self, timeout: Duration) -> Option<Event> {
+        // There is no event loop to wake up early, so wait for the whole timeout.
+        self.poll_event().or_else(|| {
+            std::thread::sleep(timeout);
+            self.poll_event()
+        })
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        if let Some(debug_state) = &self.debug_state {
+            debug_state.drain_events(&mut self.events);
+        }
+        self.events.pop_front()
+    }
+}
+
+impl OpenGLWindow for HeadlessWindow {
+    fn get_proc_address(&mut self, proc_name: &str) -> ProcAddress {
+        loader::get_proc_address(&self.display, proc_name)
+    }
+
+    fn is_current(&self) -> bool {
+        self.ctx.is_current()
+    }
+
+    fn make_current(&mut self) {
+        #[allow(unreachable_patterns)]
+        match (&self.surface, &self.ctx) {
+            (Some(surface), ctx) => {let _ = ctx.make_current(surface);}
+            (None, glutin::context::PossiblyCurrentContext::Egl(ctx)) => {
+                let _ = ctx.make_current_surfaceless();
+            }
+            (None, _) => {}
+        }
+    }
+}
+
+impl BuildFromWindowSettings for HeadlessWindow {
+    fn build_from_window_settings(settings: &WindowSettings)
+    -> Result<Self, Box<dyn Error>> {
+        Ok(HeadlessWindow::new(settings)?)
+    }
+}
@@ -4,16 +4,18 @@
 
 extern crate glutin;
 extern crate gl;
-extern crate glutin_winit;
 extern crate input;
 extern crate raw_window_handle;
 extern crate window;
 extern crate winit;
 extern crate shader_version;
 extern crate rustc_hash;
+#[cfg(feature = "log")]
+extern crate log;
 
 use rustc_hash::FxHashMap;
 
+use std::any::Any;
 use std::collections::VecDeque;
 use std::error::Error;
 
@@ -30,6 +32,7 @@ use input::{
     Input,
     ResizeArgs,
 };
+use input::event_id::EventId;
 use window::{
     BuildFromWindowSettings,
     OpenGLWindow,
@@ -44,18 +47,80 @@ use window::{
 };
 use winit::{
     application::ApplicationHandler,
-    dpi::{LogicalPosition, LogicalSize},
-    event_loop::{ActiveEventLoop, EventLoop},
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+    event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy},
+    platform::pump_events::PumpStatus,
     event::{DeviceId, ElementState, MouseScrollDelta, WindowEvent},
     window::WindowId,
 };
 use glutin::context::PossiblyCurrentGlContext;
 use glutin::display::GlDisplay;
 use glutin::prelude::GlSurface;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
 use std::time::Duration;
 use std::sync::Arc;
 
 pub use shader_version::OpenGL;
+pub use debug::{
+    DebugMessage,
+    DebugOutput,
+    DebugSettings,
+    DebugSeverity,
+    DebugSource,
+    DebugType,
+    DEBUG_MESSAGE,
+};
+
+#[cfg(all(any(windows, unix), not(target_os = "macos"), not(target_os = "ios")))]
+pub use headless::HeadlessWindow;
+pub use loader::GlLoader;
+pub use shared_context::{CurrentSharedContext, SharedContext};
+
+/// OpenGL functions loaded into a struct, generated by `gl_generator`.
+///
+/// Use [`GlLoader::gl`] to load the functions of a specific context.
+#[cfg(feature = "gl_struct")]
+#[allow(missing_docs, clippy::all)]
+pub mod gl_struct {
+    include!(concat!(env!("OUT_DIR"), "/gl_struct.rs"));
+}
+
+mod debug;
+#[cfg(all(any(windows, unix), not(target_os = "macos"), not(target_os = "ios")))]
+mod headless;
+mod loader;
+mod shared_context;
+#[cfg(feature = "softbuffer")]
+mod software;
+
+/// The name of the OpenGL graphics API.
+pub const OPENGL: &str = "OpenGL";
+/// The name of the OpenGL ES graphics API.
+///
+/// Use this as the API name in `WindowSettings::graphics_api` to request OpenGL ES.
+pub const OPENGL_ES: &str = "OpenGL ES";
+/// The name used for requesting a window without a graphics API.
+///
+/// Use this as the API name in `WindowSettings::graphics_api`
+/// to create only the window, e.g. for rendering with a software renderer.
+pub const NO_GRAPHICS_API: &str = "None";
+
+/// The event id sent when the window surface is lost, e.g. when the application is suspended.
+///
+/// The event is `Event::Custom` with an empty payload.
+/// The OpenGL context is not current until the surface is restored.
+pub const SURFACE_LOST: EventId = EventId("glutin_window/surface_lost");
+/// The event id sent when the window surface is restored after being lost.
+///
+/// The event is `Event::Custom` with an empty payload.
+/// The OpenGL context is reused and made current again.
+/// Renderers should check for lost resources, e.g. using robust context reset notifications,
+/// and upload them again when needed.
+///
+/// When the surface can not be restored, this event is not sent
+/// and the error is returned by `GlutinWindow::last_error`.
+pub const SURFACE_RESTORED: EventId = EventId("glutin_window/surface_restored");
 
 
 /// Settings for whether to ignore modifiers and use standard keyboard layouts instead.
@@ -108,13 +173,19 @@ pub struct GlutinWindow {
     /// The OpenGL context.
     pub ctx: Option<glutin::context::PossiblyCurrentContext>,
     /// The window surface.
-    pub surface: Option<glutin::surface::Surface<glutin::surface::WindowSurface>>,
+    ///
+    /// This is shared with the event loop of the window,
+    /// such that the surface can be dropped as soon as the application is suspended.
+    /// Do not keep it borrowed while polling events.
+    pub surface: Rc<RefCell<Option<glutin::surface::Surface<glutin::surface::WindowSurface>>>>,
     /// The graphics display.
     pub display: Option<glutin::display::Display>,
-    /// The event loop of the window.
+    /// The event loop owned by the window.
     ///
-    /// This is optional because when pumping events using `ApplicationHandler`,
-    /// the event loop can not be owned by `WinitWindow`.
+    /// This is only set for windows created with [`GlutinWindow::from_event_loop`].
+    /// Windows created with [`GlutinWindow::new`] use the shared event loop
+    /// returned by [`GlutinEventLoop::global`], so this is `None`.
+    /// Use [`GlutinWindow::event_sender`] to wake up or post events to the window.
     pub event_loop: Option<EventLoop<UserEvent>>,
     /// Sets keyboard layout.
     ///
@@ -153,17 +224,1152 @@ pub struct GlutinWindow {
     last_key_pressed: Option<input::Key>,
     // Stores list of events ready for processing.
     events: VecDeque<Event>,
+    // Stores the error when creating the window failed.
+    create_error: Option<GlutinWindowError>,
+    // Stores the error when restoring the surface after being suspended failed.
+    surface_error: Option<GlutinWindowError>,
+    // Window settings that could not be honored.
+    unsupported_settings: Vec<UnsupportedSetting>,
+    // Stores the windowed position and size while in fullscreen.
+    windowed_geometry: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    // Picks the OpenGL config when creating the window.
+    config_picker: Box<dyn ConfigPicker>,
+    // The minimum requirements of the OpenGL config.
+    config_requirements: ConfigRequirements,
+    // Information about the created OpenGL context.
+    context_info: Option<ContextInfo>,
+    // The APIs to try when the requested one fails.
+    context_fallbacks: Vec<glutin::context::ContextApi>,
+    // The flags used when creating the OpenGL context.
+    context_flags: ContextFlags,
+    // Settings for OpenGL debug output, taken when the context is created.
+    debug_settings: Option<DebugSettings>,
+    // The state of the installed OpenGL debug callback.
+    //
+    // Must be kept alive while the context is in use.
+    debug_state: Option<Box<debug::DebugState>>,
+    // The event loop shared with other windows.
+    shared_event_loop: Option<GlutinEventLoop>,
+    // Used to wake up the event loop when polling.
+    event_loop_proxy: EventLoopProxy<UserEvent>,
+    // The exit code of the event loop, set when the event loop exited.
+    exit_code: Option<i32>,
+    // Whether to resize the window surface when the window is resized.
+    automatic_surface_resize: bool,
+    // Whether to fall back to a CPU framebuffer when OpenGL is not available.
+    #[cfg(feature = "softbuffer")]
+    software_fallback: bool,
+    // The CPU framebuffer used instead of OpenGL.
+    #[cfg(feature = "softbuffer")]
+    software_frame: Option<software::SoftwareFrame>,
+}
+
+/// An event loop shared by multiple windows.
+///
+/// Winit allows only one event loop per process.
+/// Use this to create several windows, where each window receives its own events.
+/// Events are routed by window id when any of the windows polls for events.
+///
+/// This is cheap to clone, since it is a reference to the same event loop.
+#[derive(Clone)]
+pub struct GlutinEventLoop {
+    inner: Rc<RefCell<SharedEventLoop>>,
+}
+
+thread_local! {
+    // The event loop used by windows that are built without an explicit event loop.
+    static GLOBAL_EVENT_LOOP: RefCell<Option<GlutinEventLoop>> = const {RefCell::new(None)};
+}
+
+struct SharedEventLoop {
+    event_loop: EventLoop<UserEvent>,
+    // Whether the event loop has been resumed, which is required to create windows.
+    resumed: bool,
+    // Stores pending events for each window.
+    queues: FxHashMap<WindowId, VecDeque<RoutedEvent>>,
+    // The surface of each window, dropped when the event loop is suspended.
+    //
+    // Weak references are kept, such that a surface is dropped with its window.
+    surfaces: FxHashMap<WindowId, Weak<SurfaceSlot>>,
+    // The exit code of the event loop, set when the event loop exited.
+    exit_code: Option<i32>,
+}
+
+// The window surface, shared between a window and its event loop.
+type SurfaceSlot = RefCell<Option<glutin::surface::Surface<glutin::surface::WindowSurface>>>;
+
+// An event routed to a window of a shared event loop.
+enum RoutedEvent {
+    Window(WindowEvent),
+    Custom(EventId, Arc<dyn Any + Send + Sync>),
+    // The surface of the window was dropped when the event loop was suspended.
+    Suspended,
+    Resumed,
 }
 
-fn graphics_api_from_settings(settings: &WindowSettings) -> Result<Api, Box<dyn Error>> {
+impl GlutinEventLoop {
+    /// Creates a new event loop.
+    pub fn new() -> Result<GlutinEventLoop, winit::error::EventLoopError> {
+        Ok(GlutinEventLoop::from_event_loop(EventLoop::with_user_event().build()?))
+    }
+
+    /// Returns the event loop used by [`GlutinWindow::new`].
+    ///
+    /// The event loop is created on first use and kept for the rest of the process,
+    /// such that windows can be opened again after previous ones were closed.
+    /// Returns an error when another event loop was already created,
+    /// or when called from a thread that does not own the event loop.
+    pub fn global() -> Result<GlutinEventLoop, winit::error::EventLoopError> {
+        GLOBAL_EVENT_LOOP.with(|global| {
+            let mut global = global.borrow_mut();
+            if let Some(event_loop) = &*global {
+                return Ok(event_loop.clone());
+            }
+            let event_loop = GlutinEventLoop::new()?;
+            *global = Some(event_loop.clone());
+            Ok(event_loop)
+        })
+    }
+
+    /// Creates a shared event loop from a pre-existing Winit event loop.
+    pub fn from_event_loop(event_loop: EventLoop<UserEvent>) -> GlutinEventLoop {
+        GlutinEventLoop {
+            inner: Rc::new(RefCell::new(SharedEventLoop {
+                event_loop,
+                resumed: false,
+                queues: FxHashMap::default(),
+                surfaces: FxHashMap::default(),
+                exit_code: None,
+            }))
+        }
+    }
+
+    /// Returns the exit code when the event loop exited.
+    ///
+    /// An event loop that exited can not create windows or deliver events.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.inner.borrow().exit_code
+    }
+
+    /// Creates a new window using this event loop.
+    ///
+    /// Use [`GlutinWindowBuilder::build_shared`] for options that are specific to this back-end.
+    pub fn create_window(&self, settings: &WindowSettings) -> Result<GlutinWindow, GlutinWindowError> {
+        GlutinWindowBuilder::new(settings).build_shared(self)
+    }
+}
+
+// Routes events of a shared event loop to the queues of each window.
+struct EventRouter<'a> {
+    resumed: &'a mut bool,
+    queues: &'a mut FxHashMap<WindowId, VecDeque<RoutedEvent>>,
+    surfaces: &'a mut FxHashMap<WindowId, Weak<SurfaceSlot>>,
+    // A window waiting to be created.
+    create: Option<&'a mut GlutinWindow>,
+}
+
+impl<'a> EventRouter<'a> {
+    fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+        if !*self.resumed {return}
+
+        if let Some(w) = self.create.take() {
+            match w.create_window_and_context(event_loop) {
+                Ok(()) => {
+                    let id = w.get_window_ref().id();
+                    self.queues.insert(id, VecDeque::new());
+                    self.surfaces.insert(id, Rc::downgrade(&w.surface));
+                }
+                Err(err) => w.create_error = Some(err),
+            }
+        }
+    }
+}
+
+impl<'a> ApplicationHandler<UserEvent> for EventRouter<'a> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        *self.resumed = true;
+        for queue in self.queues.values_mut() {
+            queue.push_back(RoutedEvent::Resumed);
+        }
+        self.create_window(event_loop);
+    }
+
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        *self.resumed = false;
+        // Some platforms require the surfaces to be dropped before returning.
+        for (id, surface) in self.surfaces.iter() {
+            let surface = match surface.upgrade() {
+                Some(x) => x,
+                None => continue,
+            };
+            if surface.borrow_mut().take().is_some() {
+                if let Some(queue) = self.queues.get_mut(id) {
+                    queue.push_back(RoutedEvent::Suspended);
+                }
+            }
+        }
+    }
+
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, _cause: winit::event::StartCause) {
+        self.create_window(event_loop);
+    }
+
+    fn window_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        // Stop routing events to a window that is destroyed.
+        if let WindowEvent::Destroyed = event {
+            self.queues.remove(&window_id);
+            self.surfaces.remove(&window_id);
+            return;
+        }
+        if let Some(queue) = self.queues.get_mut(&window_id) {
+            queue.push_back(RoutedEvent::Window(event));
+        }
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+        if let UserEvent::Custom {window_id, id, payload} = event {
+            if let Some(queue) = self.queues.get_mut(&window_id) {
+                queue.push_back(RoutedEvent::Custom(id, payload));
+            }
+        }
+    }
+}
+
+/// Flags used when creating the OpenGL context.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct ContextFlags {
+    /// The OpenGL profile.
+    ///
+    /// When `None`, the profile is chosen by the driver.
+    /// This is ignored for OpenGL ES.
+    pub profile: Option<glutin::context::GlProfile>,
+    /// Whether to create a debug context.
+    pub debug: bool,
+    /// The robustness of the context.
+    ///
+    /// Use `Robustness::NoError` to request a `KHR_no_error` context.
+    pub robustness: glutin::context::Robustness,
+}
+
+/// A failed attempt to create an OpenGL context.
+///
+/// See [`GlutinWindowError::ContextCreation`].
+#[derive(Debug)]
+pub struct ContextAttempt {
+    /// The API that was requested.
+    pub api: glutin::context::ContextApi,
+    /// The reason the context could not be created.
+    pub error: glutin::error::Error,
+}
+
+impl std::fmt::Display for ContextAttempt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use glutin::context::ContextApi;
+
+        let (name, version) = match self.api {
+            ContextApi::OpenGl(v) => (OPENGL, v),
+            ContextApi::Gles(v) => (OPENGL_ES, v),
+        };
+        match version {
+            Some(v) => write!(f, "{} {}.{}: {}", name, v.major, v.minor, self.error),
+            None => write!(f, "{}: {}", name, self.error),
+        }
+    }
+}
+
+/// Information about the created OpenGL context and config.
+///
+/// See [`GlutinWindow::context_info`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ContextInfo {
+    /// The graphics API and version of the context.
+    ///
+    /// The API is either `"OpenGL"` or `"OpenGL ES"`.
+    pub api: Api,
+    /// The OpenGL profile, if known.
+    ///
+    /// This is `None` for OpenGL ES and for OpenGL versions before 3.2.
+    pub profile: Option<glutin::context::GlProfile>,
+    /// The number of samples in the multisample buffer.
+    pub samples: u8,
+    /// The number of bits of the depth buffer.
+    pub depth_bits: u8,
+    /// The number of bits of the stencil buffer.
+    pub stencil_bits: u8,
+    /// The number of bits for the alpha channel.
+    pub alpha_bits: u8,
+    /// Whether the config is sRGB capable.
+    pub srgb: bool,
+    /// Whether the context is a debug context.
+    pub debug: bool,
+    /// The robustness of the context.
+    ///
+    /// This is `Robustness::NotRobust` when the context flags can not be queried,
+    /// which requires OpenGL 3.0 or OpenGL ES 3.2.
+    pub robustness: glutin::context::Robustness,
+    /// The `GL_VENDOR` string.
+    pub vendor: String,
+    /// The `GL_RENDERER` string.
+    pub renderer: String,
+    /// The `GL_VERSION` string.
+    pub version: String,
+}
+
+impl ContextInfo {
+    // Reads the context information from the current OpenGL context.
+    //
+    // Requires the OpenGL function pointers to be loaded.
+    fn new(
+        context_api: glutin::context::ContextApi,
+        gl_config: &glutin::config::Config,
+    ) -> ContextInfo {
+        use glutin::config::GlConfig;
+        use glutin::context::{ContextApi, GlProfile, Robustness};
+
+        // From `KHR_no_error`, which is not included in the `gl` crate.
+        const CONTEXT_FLAG_NO_ERROR_BIT: gl::types::GLenum = 0x0000_0008;
+
+        fn get_integer(name: gl::types::GLenum) -> gl::types::GLenum {
+            let mut value = 0;
+            unsafe {gl::GetIntegerv(name, &mut value)};
+            value as gl::types::GLenum
+        }
+
+        fn get_string(name: gl::types::GLenum) -> String {
+            use std::ffi::CStr;
+
+            unsafe {
+                let ptr = gl::GetString(name);
+                if ptr.is_null() {String::new()}
+                else {CStr::from_ptr(ptr as *const _).to_string_lossy().into_owned()}
+            }
+        }
+
+        let version = get_string(gl::VERSION);
+        let (name, requested) = match context_api {
+            ContextApi::OpenGl(v) => (OPENGL, v),
+            ContextApi::Gles(v) => (OPENGL_ES, v),
+        };
+        let [major, minor] = parse_gl_version(&version)
+            .or_else(|| requested.map(|v| [v.major as u32, v.minor as u32]))
+            .unwrap_or([0, 0]);
+        let profile = if name == OPENGL && (major, minor) >= (3, 2) {
+            let mask = get_integer(gl::CONTEXT_PROFILE_MASK);
+            if mask & gl::CONTEXT_CORE_PROFILE_BIT != 0 {Some(GlProfile::Core)}
+            else if mask & gl::CONTEXT_COMPATIBILITY_PROFILE_BIT != 0 {
+                Some(GlProfile::Compatibility)
+            } else {None}
+        } else {None};
+        let has_context_flags = if name == OPENGL {major >= 3}
+            else {(major, minor) >= (3, 2)};
+        let flags = if has_context_flags {get_integer(gl::CONTEXT_FLAGS)} else {0};
+        let robustness = if flags & CONTEXT_FLAG_NO_ERROR_BIT != 0 {
+            Robustness::NoError
+        } else if flags & gl::CONTEXT_FLAG_ROBUST_ACCESS_BIT != 0 {
+            if get_integer(gl::RESET_NOTIFICATION_STRATEGY) == gl::LOSE_CONTEXT_ON_RESET {
+                Robustness::RobustLoseContextOnReset
+            } else {
+                Robustness::RobustNoResetNotification
+            }
+        } else {
+            Robustness::NotRobust
+        };
+
+        ContextInfo {
+            api: Api {api: name.into(), major, minor},
+            profile,
+            samples: gl_config.num_samples(),
+            depth_bits: gl_config.depth_size(),
+            stencil_bits: gl_config.stencil_size(),
+            alpha_bits: gl_config.alpha_size(),
+            srgb: gl_config.srgb_capable(),
+            debug: flags & gl::CONTEXT_FLAG_DEBUG_BIT != 0,
+            robustness,
+            vendor: get_string(gl::VENDOR),
+            renderer: get_string(gl::RENDERER),
+            version,
+        }
+    }
+}
+
+// Creates an OpenGL context by trying each API in order.
+//
+// Returns the first context that could be created,
+// or an error listing every failed attempt.
+fn create_context(
+    display: &glutin::display::Display,
+    gl_config: &glutin::config::Config,
+    raw_window_handle: Option<raw_window_handle::RawWindowHandle>,
+    apis: impl Iterator<Item = glutin::context::ContextApi>,
+    flags: &ContextFlags,
+    shared_with: Option<&glutin::context::PossiblyCurrentContext>,
+) -> Result<glutin::context::NotCurrentContext, GlutinWindowError> {
+    use glutin::context::ContextApi;
+
+    let mut attempts = vec![];
+    for api in apis {
+        let mut builder = glutin::context::ContextAttributesBuilder::new()
+            .with_context_api(api)
+            .with_debug(flags.debug)
+            .with_robustness(flags.robustness);
+        if let (Some(profile), ContextApi::OpenGl(_)) = (flags.profile, api) {
+            builder = builder.with_profile(profile);
+        }
+        if let Some(ctx) = shared_with {
+            builder = builder.with_sharing(ctx);
+        }
+        let context_attributes = builder.build(raw_window_handle);
+        match unsafe {display.create_context(gl_config, &context_attributes)} {
+            Ok(x) => return Ok(x),
+            Err(error) => attempts.push(ContextAttempt {api, error}),
+        }
+    }
+    Err(GlutinWindowError::ContextCreation(attempts))
+}
+
+// Creates the display of the event loop.
+//
+// Prefers GLX over EGL on X11 and WGL over EGL on Windows.
+fn create_display(
+    event_loop: &ActiveEventLoop,
+) -> Result<glutin::display::Display, GlutinWindowError> {
+    use glutin::display::DisplayApiPreference;
+    use raw_window_handle::HasDisplayHandle;
+
+    #[cfg(all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))]
+    let preference = DisplayApiPreference::GlxThenEgl(
+        Box::new(winit::platform::x11::register_xlib_error_hook)
+    );
+    #[cfg(target_os = "android")]
+    let preference = DisplayApiPreference::Egl;
+    #[cfg(target_os = "macos")]
+    let preference = DisplayApiPreference::Cgl;
+    #[cfg(windows)]
+    let preference = DisplayApiPreference::WglThenEgl(None);
+
+    let raw_display_handle = event_loop.display_handle()
+        .map_err(GlutinWindowError::WindowHandle)?
+        .as_raw();
+    unsafe {glutin::display::Display::new(raw_display_handle, preference)}
+        .map_err(GlutinWindowError::DisplayCreation)
+}
+
+// Parses the major and minor version from a `GL_VERSION` string.
+//
+// OpenGL uses the format `<major>.<minor>[.<release>] [<vendor info>]`,
+// while OpenGL ES prefixes it with `OpenGL ES `.
+fn parse_gl_version(version: &str) -> Option<[u32; 2]> {
+    let version = version.split_whitespace()
+        .find(|s| s.starts_with(|c: char| c.is_ascii_digit()))?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?
+        .trim_end_matches(|c: char| !c.is_ascii_digit())
+        .parse().ok()?;
+    Some([major, minor])
+}
+
+/// Minimum requirements of the OpenGL config.
+///
+/// Configs that do not satisfy these requirements are never picked.
+///
+/// The default matches Glutin's config template,
+/// with 8 bits per color channel and alpha, 24 depth bits and 8 stencil bits.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ConfigRequirements {
+    /// The minimum number of bits for the red, green and blue channels.
+    pub color_bits: [u8; 3],
+    /// The minimum number of bits for the alpha channel.
+    pub alpha_bits: u8,
+    /// The minimum number of bits of the depth buffer.
+    pub depth_bits: u8,
+    /// The minimum number of bits of the stencil buffer.
+    pub stencil_bits: u8,
+    /// Whether the config must be hardware accelerated.
+    ///
+    /// When `None`, both hardware accelerated and software configs are accepted.
+    pub hardware_accelerated: Option<bool>,
+}
+
+impl Default for ConfigRequirements {
+    fn default() -> ConfigRequirements {
+        ConfigRequirements {
+            color_bits: [8, 8, 8],
+            alpha_bits: 8,
+            depth_bits: 24,
+            stencil_bits: 8,
+            hardware_accelerated: None,
+        }
+    }
+}
+
+impl ConfigRequirements {
+    /// Returns `true` if the config satisfies the requirements.
+    pub fn is_satisfied_by(&self, config: &glutin::config::Config) -> bool {
+        use glutin::config::{ColorBufferType, GlConfig};
+
+        let color = match config.color_buffer_type() {
+            Some(ColorBufferType::Rgb {r_size, g_size, b_size}) =>
+                r_size >= self.color_bits[0] &&
+                g_size >= self.color_bits[1] &&
+                b_size >= self.color_bits[2],
+            // The format can not be identified.
+            None => true,
+            _ => false,
+        };
+        color &&
+        config.alpha_size() >= self.alpha_bits &&
+        config.depth_size() >= self.depth_bits &&
+        config.stencil_size() >= self.stencil_bits &&
+        self.hardware_accelerated.is_none_or(|hw| config.hardware_accelerated() == hw)
+    }
+}
+
+impl std::fmt::Display for ConfigRequirements {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let [r, g, b] = self.color_bits;
+        write!(f, "color bits {}/{}/{}, alpha bits {}, depth bits {}, stencil bits {}",
+            r, g, b, self.alpha_bits, self.depth_bits, self.stencil_bits)?;
+        match self.hardware_accelerated {
+            Some(true) => write!(f, ", hardware accelerated"),
+            Some(false) => write!(f, ", not hardware accelerated"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Builds a Glutin window with options that are specific to this back-end.
+///
+/// Options that are common to all back-ends are set in `WindowSettings`.
+pub struct GlutinWindowBuilder {
+    settings: WindowSettings,
+    config_picker: Box<dyn ConfigPicker>,
+    config_requirements: ConfigRequirements,
+    context_fallbacks: Vec<glutin::context::ContextApi>,
+    context_flags: ContextFlags,
+    debug_settings: Option<DebugSettings>,
+    automatic_surface_resize: bool,
+    #[cfg(feature = "softbuffer")]
+    software_fallback: bool,
+}
+
+impl GlutinWindowBuilder {
+    /// Creates a new builder from window settings.
+    pub fn new(settings: &WindowSettings) -> GlutinWindowBuilder {
+        GlutinWindowBuilder {
+            settings: settings.clone(),
+            config_picker: Box::new(DefaultConfigPicker),
+            config_requirements: ConfigRequirements::default(),
+            context_fallbacks: vec![
+                glutin::context::ContextApi::Gles(None),
+                glutin::context::ContextApi::OpenGl(Some(glutin::context::Version::new(2, 1))),
+            ],
+            context_flags: ContextFlags::default(),
+            debug_settings: None,
+            automatic_surface_resize: true,
+            #[cfg(feature = "softbuffer")]
+            software_fallback: false,
+        }
+    }
+
+    /// Sets the output of OpenGL debug messages.
+    ///
+    /// The debug callback is installed only when a debug context is requested,
+    /// see [`GlutinWindowBuilder::debug`].
+    pub fn debug_messages(mut self, value: DebugSettings) -> Self {
+        self.debug_settings = Some(value);
+        self
+    }
+
+    /// Sets the flags used when creating the OpenGL context.
+    pub fn context_flags(mut self, value: ContextFlags) -> Self {
+        self.context_flags = value;
+        self
+    }
+
+    /// Sets the OpenGL profile.
+    ///
+    /// When `None`, the profile is chosen by the driver.
+    pub fn profile(mut self, value: Option<glutin::context::GlProfile>) -> Self {
+        self.context_flags.profile = value;
+        self
+    }
+
+    /// Sets whether to create a debug context.
+    pub fn debug(mut self, value: bool) -> Self {
+        self.context_flags.debug = value;
+        self
+    }
+
+    /// Sets the robustness of the context.
+    ///
+    /// Use `Robustness::NoError` to request a `KHR_no_error` context.
+    pub fn robustness(mut self, value: glutin::context::Robustness) -> Self {
+        self.context_flags.robustness = value;
+        self
+    }
+
+    /// Sets whether to resize the window surface when the window is resized.
+    ///
+    /// This is enabled by default.
+    /// Disable it to resize `GlutinWindow::surface` manually on `Input::Resize`.
+    pub fn automatic_surface_resize(mut self, value: bool) -> Self {
+        self.automatic_surface_resize = value;
+        self
+    }
+
+    /// Sets whether to fall back to a CPU framebuffer when no OpenGL config is available.
+    ///
+    /// The framebuffer is presented with `softbuffer`,
+    /// see [`GlutinWindow::software_frame`].
+    /// This is disabled by default.
+    #[cfg(feature = "softbuffer")]
+    pub fn software_fallback(mut self, value: bool) -> Self {
+        self.software_fallback = value;
+        self
+    }
+
+    /// Sets the APIs to try in order when the requested API is not available.
+    ///
+    /// By default, OpenGL ES of any version is tried, followed by OpenGL 2.1.
+    /// Use an empty list to fail when the requested API is not available.
+    pub fn context_fallbacks(mut self, value: Vec<glutin::context::ContextApi>) -> Self {
+        self.context_fallbacks = value;
+        self
+    }
+
+    /// Sets the minimum requirements of the OpenGL config.
+    pub fn config_requirements(mut self, value: ConfigRequirements) -> Self {
+        self.config_requirements = value;
+        self
+    }
+
+    /// Sets the minimum number of bits for the red, green and blue channels.
+    pub fn color_bits(mut self, red: u8, green: u8, blue: u8) -> Self {
+        self.config_requirements.color_bits = [red, green, blue];
+        self
+    }
+
+    /// Sets the minimum number of bits for the alpha channel.
+    pub fn alpha_bits(mut self, value: u8) -> Self {
+        self.config_requirements.alpha_bits = value;
+        self
+    }
+
+    /// Sets the minimum number of bits of the depth buffer.
+    ///
+    /// The default is 24. Use 0 when no depth buffer is needed.
+    pub fn depth_bits(mut self, value: u8) -> Self {
+        self.config_requirements.depth_bits = value;
+        self
+    }
+
+    /// Sets the minimum number of bits of the stencil buffer.
+    ///
+    /// The default is 8. Use 0 when no stencil buffer is needed.
+    pub fn stencil_bits(mut self, value: u8) -> Self {
+        self.config_requirements.stencil_bits = value;
+        self
+    }
+
+    /// Sets whether the config must be hardware accelerated.
+    ///
+    /// When `None`, both hardware accelerated and software configs are accepted.
+    pub fn hardware_accelerated(mut self, value: Option<bool>) -> Self {
+        self.config_requirements.hardware_accelerated = value;
+        self
+    }
+
+    /// Sets the strategy for picking the OpenGL config.
+    ///
+    /// The picker only receives configs that satisfy the config requirements.
+    /// The default is [`DefaultConfigPicker`].
+    pub fn config_picker<P: ConfigPicker + 'static>(mut self, picker: P) -> Self {
+        self.config_picker = Box::new(picker);
+        self
+    }
+
+    /// Builds the window.
+    ///
+    /// The window uses the event loop returned by [`GlutinEventLoop::global`],
+    /// so a new window can be built after a previous one was closed.
+    pub fn build(self) -> Result<GlutinWindow, Box<dyn Error>> {
+        let event_loop = GlutinEventLoop::global()?;
+        Ok(self.build_shared(&event_loop)?)
+    }
+
+    /// Builds the window from a pre-existing Glutin event loop.
+    ///
+    /// Returns an error when the window or its OpenGL context can not be created.
+    pub fn build_from_event_loop(
+        self,
+        event_loop: winit::event_loop::EventLoop<UserEvent>,
+    ) -> Result<GlutinWindow, GlutinWindowError> {
+        self.build_with(Some(event_loop), None)
+    }
+
+    /// Builds the window using an event loop shared with other windows.
+    ///
+    /// Returns an error when the window or its OpenGL context can not be created.
+    pub fn build_shared(
+        self,
+        event_loop: &GlutinEventLoop,
+    ) -> Result<GlutinWindow, GlutinWindowError> {
+        self.build_with(None, Some(event_loop.clone()))
+    }
+
+    fn build_with(
+        self,
+        event_loop: Option<winit::event_loop::EventLoop<UserEvent>>,
+        shared_event_loop: Option<GlutinEventLoop>,
+    ) -> Result<GlutinWindow, GlutinWindowError> {
+        let settings = self.settings;
+        let title = settings.get_title();
+        let exit_on_esc = settings.get_exit_on_esc();
+        let automatic_close = settings.get_automatic_close();
+        let event_loop_proxy = match (&event_loop, &shared_event_loop) {
+            (Some(event_loop), _) => event_loop.create_proxy(),
+            (None, Some(shared)) => shared.inner.borrow().event_loop.create_proxy(),
+            (None, None) => unreachable!("The window is built with an event loop"),
+        };
+
+        let mut w = GlutinWindow {
+            ctx: None,
+            display: None,
+            surface: Rc::new(RefCell::new(None)),
+            window: None,
+            title,
+            exit_on_esc,
+            settings,
+            should_close: false,
+            automatic_close,
+            cursor_pos: None,
+            is_capturing_cursor: false,
+            last_cursor_pos: None,
+            mouse_relative: None,
+            last_key_pressed: None,
+            event_loop,
+            keyboard_ignore_modifiers: KeyboardIgnoreModifiers::None,
+            events: VecDeque::new(),
+            create_error: None,
+            surface_error: None,
+            unsupported_settings: vec![],
+            windowed_geometry: None,
+            config_picker: self.config_picker,
+            config_requirements: self.config_requirements,
+            context_info: None,
+            context_fallbacks: self.context_fallbacks,
+            context_flags: self.context_flags,
+            debug_settings: self.debug_settings,
+            debug_state: None,
+            shared_event_loop,
+            event_loop_proxy,
+            exit_code: None,
+            automatic_surface_resize: self.automatic_surface_resize,
+            #[cfg(feature = "softbuffer")]
+            software_fallback: self.software_fallback,
+            #[cfg(feature = "softbuffer")]
+            software_frame: None,
+
+            devices: 0,
+            device_id_map: FxHashMap::default(),
+        };
+        // Causes the window to be created through `ApplicationHandler::request_redraw`.
+        if let Some(e) = w.poll_event() {w.events.push_front(e)}
+        if let Some(err) = w.create_error.take() {
+            return Err(err);
+        }
+        if let Some(code) = w.exit_code {
+            return Err(GlutinWindowError::EventLoopExited(code));
+        }
+        Ok(w)
+    }
+}
+
+/// Picks the OpenGL config to use among the candidates found by Glutin.
+///
+/// This is implemented for closures of the same signature as `pick`.
+pub trait ConfigPicker {
+    /// Returns the config to use, or `None` when no candidate is suitable.
+    fn pick(
+        &mut self,
+        settings: &WindowSettings,
+        configs: &mut dyn Iterator<Item = glutin::config::Config>,
+    ) -> Option<glutin::config::Config>;
+}
+
+impl<F> ConfigPicker for F
+    where F: FnMut(
+        &WindowSettings,
+        &mut dyn Iterator<Item = glutin::config::Config>,
+    ) -> Option<glutin::config::Config>
+{
+    fn pick(
+        &mut self,
+        settings: &WindowSettings,
+        configs: &mut dyn Iterator<Item = glutin::config::Config>,
+    ) -> Option<glutin::config::Config> {
+        self(settings, configs)
+    }
+}
+
+/// The default strategy for picking the OpenGL config.
+///
+/// Picks the config with the number of samples closest to `WindowSettings::get_samples`.
+/// When transparency is requested, configs that support transparency are preferred.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DefaultConfigPicker;
+
+impl ConfigPicker for DefaultConfigPicker {
+    fn pick(
+        &mut self,
+        settings: &WindowSettings,
+        configs: &mut dyn Iterator<Item = glutin::config::Config>,
+    ) -> Option<glutin::config::Config> {
+        use glutin::config::GlConfig;
+
+        let transparent = settings.get_transparent();
+        let samples = settings.get_samples();
+        configs.min_by_key(|config| {
+            let no_transparency = transparent && !config.supports_transparency().unwrap_or(false);
+            (no_transparency, config.num_samples().abs_diff(samples))
+        })
+    }
+}
+
+/// Fullscreen mode of the window.
+///
+/// See [`GlutinWindow::set_fullscreen`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Fullscreen {
+    /// A normal window.
+    Windowed,
+    /// A borderless window covering the monitor,
+    /// using the current video mode of the monitor.
+    Borderless,
+    /// Exclusive fullscreen, changing the video mode of the monitor.
+    Exclusive,
+}
+
+/// Information about a monitor.
+///
+/// See [`GlutinWindow::monitors`] and [`GlutinWindow::current_monitor`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct MonitorInfo {
+    /// The name of the monitor, if available.
+    pub name: Option<String>,
+    /// The position of the top-left corner in physical pixels.
+    pub position: [i32; 2],
+    /// The size in physical pixels.
+    pub size: [u32; 2],
+    /// The scale factor used to map logical pixels to physical pixels.
+    pub scale_factor: f64,
+    /// The refresh rate in millihertz, if available.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// The video modes that can be used for exclusive fullscreen.
+    pub video_modes: Vec<VideoModeInfo>,
+    // The Winit monitor.
+    handle: winit::monitor::MonitorHandle,
+}
+
+impl MonitorInfo {
+    fn new(handle: winit::monitor::MonitorHandle) -> MonitorInfo {
+        let position = handle.position();
+        let size = handle.size();
+        MonitorInfo {
+            name: handle.name(),
+            position: [position.x, position.y],
+            size: [size.width, size.height],
+            scale_factor: handle.scale_factor(),
+            refresh_rate_millihertz: handle.refresh_rate_millihertz(),
+            video_modes: handle.video_modes().map(VideoModeInfo::new).collect(),
+            handle,
+        }
+    }
+}
+
+/// Information about a video mode of a monitor.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VideoModeInfo {
+    /// The resolution in physical pixels.
+    pub size: [u32; 2],
+    /// The bit depth.
+    pub bit_depth: u16,
+    /// The refresh rate in millihertz.
+    pub refresh_rate_millihertz: u32,
+    // The Winit video mode.
+    handle: winit::monitor::VideoModeHandle,
+}
+
+impl VideoModeInfo {
+    fn new(handle: winit::monitor::VideoModeHandle) -> VideoModeInfo {
+        let size = handle.size();
+        VideoModeInfo {
+            size: [size.width, size.height],
+            bit_depth: handle.bit_depth(),
+            refresh_rate_millihertz: handle.refresh_rate_millihertz(),
+            handle,
+        }
+    }
+}
+
+/// An error that occurs when switching fullscreen mode.
+#[derive(Debug)]
+pub enum FullscreenError {
+    /// There is no monitor to use for fullscreen.
+    NoMonitor,
+    /// The monitor has no video modes for exclusive fullscreen.
+    NoVideoMode,
+    /// The video mode does not belong to the target monitor.
+    VideoModeMismatch,
+    /// The platform did not apply the requested mode.
+    NotApplied,
+}
+
+impl std::fmt::Display for FullscreenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FullscreenError::NoMonitor =>
+                write!(f, "No monitor available for fullscreen"),
+            FullscreenError::NoVideoMode =>
+                write!(f, "No video mode available for exclusive fullscreen"),
+            FullscreenError::VideoModeMismatch =>
+                write!(f, "The video mode does not belong to the target monitor"),
+            FullscreenError::NotApplied =>
+                write!(f, "The platform did not apply the fullscreen mode"),
+        }
+    }
+}
+
+impl Error for FullscreenError {}
+
+/// A window setting that could not be honored when creating the window.
+///
+/// See [`GlutinWindow::unsupported_settings`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum UnsupportedSetting {
+    /// The window could not be made fullscreen.
+    Fullscreen,
+    /// The window decorations could not be set.
+    Decorated,
+    /// Whether the window is resizable could not be set.
+    Resizable,
+    /// The OpenGL config does not support transparency.
+    Transparent,
+    /// The OpenGL config is not sRGB capable.
+    Srgb,
+    /// The OpenGL config has fewer samples than requested.
+    Samples,
+}
+
+/// An error that occurs when creating the window or its OpenGL context.
+#[derive(Debug)]
+pub enum GlutinWindowError {
+    /// Glutin failed to create the display.
+    DisplayCreation(glutin::error::Error),
+    /// No OpenGL config matched the window settings.
+    NoMatchingConfig(Box<dyn Error>),
+    /// Winit failed to create the window.
+    WindowCreation(winit::error::OsError),
+    /// The native handle of the window was not available.
+    WindowHandle(raw_window_handle::HandleError),
+    /// Glutin failed to create the window surface.
+    SurfaceCreation(glutin::error::Error),
+    /// Glutin failed to create the OpenGL context.
+    ///
+    /// Contains every attempt in the order they were tried,
+    /// starting with the requested API followed by the fallbacks.
+    ContextCreation(Vec<ContextAttempt>),
+    /// The OpenGL context could not be made current.
+    MakeCurrent(glutin::error::Error),
+    /// The swap interval for vsync could not be set.
+    SwapInterval(glutin::error::Error),
+    /// The requested graphics API is not supported.
+    UnsupportedApi(UnsupportedGraphicsApiError),
+    /// The window has no OpenGL context.
+    NoContext,
+    /// The event loop exited with an exit code before the window was created.
+    EventLoopExited(i32),
+    /// OpenGL was not available and the CPU framebuffer could not be created.
+    #[cfg(feature = "softbuffer")]
+    SoftwareFallback(softbuffer::SoftBufferError),
+}
+
+impl std::fmt::Display for GlutinWindowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use GlutinWindowError as E;
+
+        match self {
+            E::DisplayCreation(err) => write!(f, "Could not create display: {}", err),
+            E::NoMatchingConfig(err) => write!(f, "No matching OpenGL config: {}", err),
+            E::WindowCreation(err) => write!(f, "Could not create window: {}", err),
+            E::WindowHandle(err) => write!(f, "Could not get window handle: {}", err),
+            E::SurfaceCreation(err) => write!(f, "Could not create window surface: {}", err),
+            E::ContextCreation(attempts) => {
+                write!(f, "Could not create OpenGL context")?;
+                for (i, attempt) in attempts.iter().enumerate() {
+                    write!(f, "{} {}", if i == 0 {":"} else {";"}, attempt)?;
+                }
+                Ok(())
+            }
+            E::MakeCurrent(err) => write!(f, "Could not make OpenGL context current: {}", err),
+            E::SwapInterval(err) => write!(f, "Could not set swap interval: {}", err),
+            E::UnsupportedApi(err) => write!(f, "{}", err),
+            E::NoContext => write!(f, "The window has no OpenGL context"),
+            E::EventLoopExited(code) => write!(f, "The event loop exited with code {}", code),
+            #[cfg(feature = "softbuffer")]
+            E::SoftwareFallback(err) => write!(f, "Could not create software framebuffer: {}", err),
+        }
+    }
+}
+
+impl Error for GlutinWindowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use GlutinWindowError as E;
+
+        match self {
+            E::NoMatchingConfig(err) => Some(&**err),
+            E::WindowCreation(err) => Some(err),
+            E::WindowHandle(err) => Some(err),
+            E::ContextCreation(attempts) =>
+                attempts.last().map(|attempt| &attempt.error as &(dyn Error + 'static)),
+            E::DisplayCreation(err) |
+            E::SurfaceCreation(err) |
+            E::MakeCurrent(err) |
+            E::SwapInterval(err) => Some(err),
+            E::UnsupportedApi(err) => Some(err),
+            E::NoContext |
+            E::EventLoopExited(_) => None,
+            #[cfg(feature = "softbuffer")]
+            E::SoftwareFallback(err) => Some(err),
+        }
+    }
+}
+
+// Returns `true` when the window settings request a graphics API without OpenGL.
+//
+// Such windows are created without an OpenGL context,
+// such that the application can use its own renderer.
+fn is_window_only(settings: &WindowSettings) -> bool {
+    match settings.get_maybe_graphics_api() {
+        Some(api) => api.is_vulkan() || api.is_directx() || api.is_metal() ||
+                     api.api == NO_GRAPHICS_API,
+        None => false,
+    }
+}
+
+// Maps the graphics API of the window settings to a Glutin context API.
+fn graphics_api_from_settings(
+    settings: &WindowSettings
+) -> Result<glutin::context::ContextApi, UnsupportedGraphicsApiError> {
+    use glutin::context::{ContextApi, Version};
+
     let api = settings.get_maybe_graphics_api().unwrap_or(Api::opengl(3, 2));
-    if api.api != "OpenGL" {
-        return Err(UnsupportedGraphicsApiError {
+    let version = Some(Version::new(api.major as u8, api.minor as u8));
+    match &*api.api {
+        OPENGL => Ok(ContextApi::OpenGl(version)),
+        OPENGL_ES => Ok(ContextApi::Gles(version)),
+        _ => Err(UnsupportedGraphicsApiError {
             found: api.api,
-            expected: vec!["OpenGL".into()]
-        }.into());
+            expected: vec![OPENGL.into(), OPENGL_ES.into()]
+        }),
+    }
+}
+
+fn window_attributes_from_settings(
+    settings: &WindowSettings
+) -> winit::window::WindowAttributes {
+    use winit::window::Fullscreen;
+
+    let size = settings.get_size();
+    winit::window::Window::default_attributes()
+        .with_inner_size(LogicalSize::<f64>::new(size.width, size.height))
+        .with_title(settings.get_title())
+        .with_fullscreen(if settings.get_fullscreen() {
+            Some(Fullscreen::Borderless(None))
+        } else {None})
+        .with_decorations(settings.get_decorated())
+        .with_resizable(settings.get_resizable())
+        .with_transparent(settings.get_transparent())
+}
+
+// Checks which settings were not honored by the created window and OpenGL config.
+//
+// Settings depending on the OpenGL config are only checked when there is a config.
+fn unsupported_settings(
+    settings: &WindowSettings,
+    window: &winit::window::Window,
+    gl_config: Option<&glutin::config::Config>,
+) -> Vec<UnsupportedSetting> {
+    use glutin::config::GlConfig;
+
+    let mut res = vec![];
+    if settings.get_fullscreen() && window.fullscreen().is_none() {
+        res.push(UnsupportedSetting::Fullscreen);
+    }
+    if settings.get_decorated() != window.is_decorated() {
+        res.push(UnsupportedSetting::Decorated);
+    }
+    if settings.get_resizable() != window.is_resizable() {
+        res.push(UnsupportedSetting::Resizable);
+    }
+    let gl_config = match gl_config {
+        Some(x) => x,
+        None => return res,
     };
-    Ok(api)
+    if settings.get_transparent() && gl_config.supports_transparency() != Some(true) {
+        res.push(UnsupportedSetting::Transparent);
+    }
+    if settings.get_srgb() && !gl_config.srgb_capable() {
+        res.push(UnsupportedSetting::Srgb);
+    }
+    if gl_config.num_samples() < settings.get_samples() {
+        res.push(UnsupportedSetting::Samples);
+    }
+    res
+}
+
+// Creates the OpenGL surface of a window.
+fn create_window_surface(
+    settings: &WindowSettings,
+    window: &winit::window::Window,
+    gl_config: &glutin::config::Config,
+) -> Result<glutin::surface::Surface<glutin::surface::WindowSurface>, GlutinWindowError> {
+    use glutin::display::GetGlDisplay;
+    use raw_window_handle::HasWindowHandle;
+    use std::num::NonZeroU32;
+
+    let raw_window_handle = window.window_handle()
+        .map_err(GlutinWindowError::WindowHandle)?
+        .as_raw();
+    let draw_size = window.inner_size();
+    // The window might have zero size, e.g. when it starts minimized.
+    let dw = NonZeroU32::new(draw_size.width).unwrap_or(NonZeroU32::MIN);
+    let dh = NonZeroU32::new(draw_size.height).unwrap_or(NonZeroU32::MIN);
+    let surface_attributes = surface_attributes_builder_from_settings(settings)
+        .build(raw_window_handle, dw, dh);
+    unsafe {gl_config.display().create_window_surface(gl_config, &surface_attributes)}
+        .map_err(GlutinWindowError::SurfaceCreation)
+}
+
+// Enables vsync when requested by the window settings.
+fn set_vsync(
+    settings: &WindowSettings,
+    surface: &glutin::surface::Surface<glutin::surface::WindowSurface>,
+    ctx: &glutin::context::PossiblyCurrentContext,
+) -> Result<(), GlutinWindowError> {
+    use std::num::NonZeroU32;
+
+    if settings.get_vsync() {
+        surface.set_swap_interval(ctx, glutin::surface::SwapInterval::Wait(NonZeroU32::MIN))
+            .map_err(GlutinWindowError::SwapInterval)?;
+    }
+    Ok(())
 }
 
 fn surface_attributes_builder_from_settings(
@@ -174,10 +1380,28 @@ fn surface_attributes_builder_from_settings(
 }
 
 fn config_template_builder_from_settings(
-    settings: &WindowSettings
+    settings: &WindowSettings,
+    requirements: &ConfigRequirements,
+    api: glutin::context::ContextApi,
 ) -> glutin::config::ConfigTemplateBuilder {
+    use glutin::config::ColorBufferType;
+    use glutin::context::ContextApi;
+
+    let [r_size, g_size, b_size] = requirements.color_bits;
     let x = glutin::config::ConfigTemplateBuilder::new()
-        .with_transparency(settings.get_transparent());
+        .with_transparency(settings.get_transparent())
+        .with_buffer_type(ColorBufferType::Rgb {r_size, g_size, b_size})
+        .with_alpha_size(requirements.alpha_bits)
+        .with_depth_size(requirements.depth_bits)
+        .with_stencil_size(requirements.stencil_bits)
+        .prefer_hardware_accelerated(requirements.hardware_accelerated);
+    // Make sure the config can be used with the requested OpenGL ES version.
+    let x = match api {
+        ContextApi::Gles(Some(v)) if v.major >= 3 => x.with_api(glutin::config::Api::GLES3),
+        ContextApi::Gles(Some(v)) if v.major == 2 => x.with_api(glutin::config::Api::GLES2),
+        ContextApi::Gles(Some(v)) if v.major == 1 => x.with_api(glutin::config::Api::GLES1),
+        _ => x,
+    };
     let samples = settings.get_samples();
     if samples == 0 {x} else {
         x.with_multisampling(samples)
@@ -186,45 +1410,201 @@ fn config_template_builder_from_settings(
 
 impl GlutinWindow {
 
-    /// Creates a new game window for Glutin.
-    pub fn new(settings: &WindowSettings) -> Result<Self, Box<dyn Error>> {
-        let event_loop = winit::event_loop::EventLoop::with_user_event().build()?;
-        Self::from_event_loop(settings, event_loop)
+    /// Creates a new game window for Glutin.
+    ///
+    /// The window uses the event loop returned by [`GlutinEventLoop::global`].
+    /// Use [`GlutinWindowBuilder`] for options that are specific to this back-end.
+    pub fn new(settings: &WindowSettings) -> Result<Self, Box<dyn Error>> {
+        GlutinWindowBuilder::new(settings).build()
+    }
+
+    /// Creates a game window from a pre-existing Glutin event loop.
+    ///
+    /// Returns an error when the window or its OpenGL context can not be created.
+    pub fn from_event_loop(
+        settings: &WindowSettings,
+        event_loop: winit::event_loop::EventLoop<UserEvent>,
+    ) -> Result<Self, GlutinWindowError> {
+        GlutinWindowBuilder::new(settings).build_from_event_loop(event_loop)
+    }
+
+    /// Returns the window settings that could not be honored.
+    ///
+    /// Some platforms or drivers do not support all window settings.
+    /// This list is empty when all settings were applied.
+    pub fn unsupported_settings(&self) -> &[UnsupportedSetting] {
+        &self.unsupported_settings
+    }
+
+    /// Returns the error of restoring the window surface when the application resumed.
+    ///
+    /// The window has no surface while this is set,
+    /// so nothing is drawn until the application is suspended and resumed again.
+    pub fn last_error(&self) -> Option<&GlutinWindowError> {
+        self.surface_error.as_ref()
+    }
+
+    /// Returns information about the created OpenGL context and config.
+    ///
+    /// This tells which graphics API and version was created,
+    /// since the back-end might fall back to another version than requested.
+    pub fn context_info(&self) -> Option<&ContextInfo> {
+        self.context_info.as_ref()
+    }
+
+    /// Returns a handle for posting custom events to this window from other threads.
+    pub fn event_sender(&self) -> EventSender {
+        EventSender {
+            proxy: self.event_loop_proxy.clone(),
+            window_id: self.get_window_ref().id(),
+        }
+    }
+
+    /// Returns the exit code when the event loop exited.
+    ///
+    /// The window is marked as should-close when the event loop exits,
+    /// e.g. when the platform terminates the loop.
+    /// Afterwards, polling returns only the events that were already queued.
+    ///
+    /// Windows that own their event loop, e.g. created with [`GlutinWindow::from_event_loop`],
+    /// also exit the event loop on `CloseRequested` with automatic close.
+    /// A shared event loop, which is used by default, keeps running when a window closes,
+    /// since other windows might still use it.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Returns the CPU framebuffer used when OpenGL is not available.
+    ///
+    /// Returns `None` unless the window fell back to software rendering,
+    /// see [`GlutinWindowBuilder::software_fallback`].
+    ///
+    /// The frame has one `u32` per pixel of the draw size, stored in rows from the top.
+    /// Pixels are in `0RGB` format, where the upper 8 bits are ignored.
+    /// The frame is kept between calls and is presented by `swap_buffers`.
+    #[cfg(feature = "softbuffer")]
+    pub fn software_frame(&mut self) -> Option<&mut [u32]> {
+        self.software_frame.as_mut().map(|frame| frame.pixels_mut())
+    }
+
+    /// Returns a loader of OpenGL functions for the context of this window.
+    ///
+    /// Returns `None` when the context is not created yet.
+    pub fn loader(&self) -> Option<GlLoader> {
+        self.display.clone().map(GlLoader::new)
+    }
+
+    /// Creates an OpenGL context that shares objects with the context of this window.
+    ///
+    /// The returned context can be sent to a worker thread and made current there,
+    /// e.g. to upload textures and meshes in the background.
+    /// It uses the same config, graphics API and context flags as the window context.
+    pub fn create_shared_context(&self) -> Result<SharedContext, GlutinWindowError> {
+        let ctx = self.ctx.as_ref().ok_or(GlutinWindowError::NoContext)?;
+        SharedContext::new(ctx, &self.context_flags)
+    }
+
+    /// Returns information about all available monitors.
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.get_window_ref().available_monitors().map(MonitorInfo::new).collect()
+    }
+
+    /// Returns information about the monitor the window is currently on.
+    ///
+    /// Returns `None` when the current monitor can not be detected.
+    pub fn current_monitor(&self) -> Option<MonitorInfo> {
+        self.get_window_ref().current_monitor().map(MonitorInfo::new)
+    }
+
+    /// Switches between windowed, borderless fullscreen and exclusive fullscreen.
+    ///
+    /// When `monitor` is `None`, the current monitor of the window is used.
+    /// For exclusive fullscreen, `video_mode` should be one of the monitor's
+    /// `video_modes`. When `video_mode` is `None`,
+    /// the largest video mode with the highest refresh rate is picked.
+    ///
+    /// The windowed position and size are remembered when entering fullscreen,
+    /// and restored when switching back to `Fullscreen::Windowed`.
+    /// The new size is reported by the resize event that follows the switch.
+    ///
+    /// Returns `FullscreenError::NotApplied` when the platform ignored the request,
+    /// e.g. exclusive fullscreen on Wayland.
+    pub fn set_fullscreen(
+        &mut self,
+        mode: Fullscreen,
+        monitor: Option<&MonitorInfo>,
+        video_mode: Option<&VideoModeInfo>,
+    ) -> Result<(), FullscreenError> {
+        let monitor = monitor.map(|m| m.handle.clone());
+        let video_mode = video_mode.map(|m| m.handle.clone());
+        let window = self.get_window();
+        let was_windowed = window.fullscreen().is_none();
+        let monitor = if mode == Fullscreen::Windowed {None} else {
+            let monitor = monitor
+                .or_else(|| window.current_monitor())
+                .or_else(|| window.primary_monitor())
+                .ok_or(FullscreenError::NoMonitor)?;
+            Some(monitor)
+        };
+
+        match (mode, monitor) {
+            (Fullscreen::Windowed, _) | (_, None) => {
+                window.set_fullscreen(None);
+                if let Some((pos, size)) = self.windowed_geometry.take() {
+                    let _ = window.request_inner_size(size);
+                    window.set_outer_position(pos);
+                } else {
+                    let size = self.settings.get_size();
+                    let _ = window.request_inner_size(LogicalSize::<f64>::new(size.width, size.height));
+                }
+            }
+            (Fullscreen::Borderless, Some(monitor)) => {
+                self.remember_windowed_geometry(was_windowed);
+                window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(Some(monitor))));
+            }
+            (Fullscreen::Exclusive, Some(monitor)) => {
+                let video_mode = match video_mode {
+                    Some(video_mode) => {
+                        if video_mode.monitor() != monitor {
+                            return Err(FullscreenError::VideoModeMismatch);
+                        }
+                        video_mode
+                    }
+                    None => monitor.video_modes()
+                        .max_by_key(|m| {
+                            let size = m.size();
+                            (size.width * size.height, m.refresh_rate_millihertz(), m.bit_depth())
+                        })
+                        .ok_or(FullscreenError::NoVideoMode)?,
+                };
+                self.remember_windowed_geometry(was_windowed);
+                window.set_fullscreen(Some(winit::window::Fullscreen::Exclusive(video_mode)));
+            }
+        }
+
+        let applied = matches!(
+            (mode, window.fullscreen()),
+            (Fullscreen::Windowed, None) |
+            (Fullscreen::Borderless, Some(winit::window::Fullscreen::Borderless(_))) |
+            (Fullscreen::Exclusive, Some(winit::window::Fullscreen::Exclusive(_)))
+        );
+        if !applied {
+            // The window stays windowed, so there is no geometry to restore.
+            if was_windowed && window.fullscreen().is_none() {
+                self.windowed_geometry = None;
+            }
+            return Err(FullscreenError::NotApplied);
+        }
+        Ok(())
     }
 
-    /// Creates a game window from a pre-existing Glutin event loop.
-    pub fn from_event_loop(
-        settings: &WindowSettings,
-        event_loop: winit::event_loop::EventLoop<UserEvent>,
-    ) -> Result<Self, Box<dyn Error>> {
-        let title = settings.get_title();
-        let exit_on_esc = settings.get_exit_on_esc();
-
-        let mut w = GlutinWindow {
-            ctx: None,
-            display: None,
-            surface: None,
-            window: None,
-            title,
-            exit_on_esc,
-            settings: settings.clone(),
-            should_close: false,
-            automatic_close: settings.get_automatic_close(),
-            cursor_pos: None,
-            is_capturing_cursor: false,
-            last_cursor_pos: None,
-            mouse_relative: None,
-            last_key_pressed: None,
-            event_loop: Some(event_loop),
-            keyboard_ignore_modifiers: KeyboardIgnoreModifiers::None,
-            events: VecDeque::new(),
+    fn remember_windowed_geometry(&mut self, was_windowed: bool) {
+        if !was_windowed {return}
 
-            devices: 0,
-            device_id_map: FxHashMap::default(),
-        };
-        // Causes the window to be created through `ApplicationHandler::request_redraw`.
-        if let Some(e) = w.poll_event() {w.events.push_front(e)}
-        Ok(w)
+        let window = self.get_window_ref();
+        if let Ok(pos) = window.outer_position() {
+            self.windowed_geometry = Some((pos, window.inner_size()));
+        }
     }
 
     /// Gets a reference to the window.
@@ -361,6 +1741,302 @@ impl GlutinWindow {
         } else {input}
     }
 
+    // Creates the window, surface and OpenGL context.
+    //
+    // This is called from `ApplicationHandler::resumed`,
+    // which can not return errors, so the error is stored in `create_error`.
+    fn create_window_and_context(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+    ) -> Result<(), GlutinWindowError> {
+        use glutin::display::GetGlDisplay;
+        use glutin::context::GlContext;
+        use glutin::context::NotCurrentGlContext;
+        use raw_window_handle::HasWindowHandle;
+
+        let settings = &self.settings;
+        if is_window_only(settings) {
+            let window = event_loop.create_window(window_attributes_from_settings(settings))
+                .map_err(GlutinWindowError::WindowCreation)?;
+            self.unsupported_settings = unsupported_settings(settings, &window, None);
+            self.window = Some(Arc::new(window));
+            return Ok(());
+        }
+
+        let config_picker = &mut self.config_picker;
+        let requirements = self.config_requirements;
+        let api = graphics_api_from_settings(settings).map_err(|mut err| {
+            // These are accepted for windows without an OpenGL context.
+            err.expected.extend(["Vulkan", "DirectX", "Metal", NO_GRAPHICS_API].map(Into::into));
+            GlutinWindowError::UnsupportedApi(err)
+        })?;
+
+        let template = config_template_builder_from_settings(settings, &requirements, api).build();
+        let gl_config = create_display(event_loop).and_then(|display| {
+            let configs = unsafe {display.find_configs(template)}
+                .map_err(|err| GlutinWindowError::NoMatchingConfig(err.into()))?;
+            let mut configs = configs.filter(|config| requirements.is_satisfied_by(config));
+            config_picker.pick(settings, &mut configs).ok_or_else(|| {
+                GlutinWindowError::NoMatchingConfig(
+                    format!("No config satisfies the requirements ({})", requirements).into()
+                )
+            })
+        });
+        let gl_config = match gl_config {
+            Ok(x) => x,
+            #[cfg(feature = "softbuffer")]
+            Err(_) if self.software_fallback => return self.create_software_window(event_loop),
+            Err(err) => return Err(err),
+        };
+
+        let window = event_loop.create_window(window_attributes_from_settings(settings))
+            .map_err(GlutinWindowError::WindowCreation)?;
+
+        let raw_window_handle = window.window_handle()
+            .map_err(GlutinWindowError::WindowHandle)?
+            .as_raw();
+        let display: glutin::display::Display = gl_config.display();
+        let surface = create_window_surface(settings, &window, &gl_config)?;
+
+        let not_current_gl_context = create_context(
+            &display,
+            &gl_config,
+            Some(raw_window_handle),
+            std::iter::once(api).chain(self.context_fallbacks.iter().cloned()),
+            &self.context_flags,
+            None,
+        )?;
+
+        let ctx: glutin::context::PossiblyCurrentContext = not_current_gl_context
+            .make_current(&surface)
+            .map_err(GlutinWindowError::MakeCurrent)?;
+        set_vsync(settings, &surface, &ctx)?;
+
+        // Load the OpenGL function pointers.
+        GlLoader::new(display.clone()).load_global();
+
+        self.context_info = Some(ContextInfo::new(ctx.context_api(), &gl_config));
+        if self.context_flags.debug {
+            if let Some(debug_settings) = self.debug_settings.take() {
+                self.debug_state = debug::DebugState::install(debug_settings);
+            }
+        }
+        self.unsupported_settings = unsupported_settings(settings, &window, Some(&gl_config));
+        self.ctx = Some(ctx);
+        *self.surface.borrow_mut() = Some(surface);
+        self.display = Some(display);
+        self.window = Some(Arc::new(window));
+        Ok(())
+    }
+
+    // Creates the window with a CPU framebuffer instead of an OpenGL context.
+    #[cfg(feature = "softbuffer")]
+    fn create_software_window(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+    ) -> Result<(), GlutinWindowError> {
+        let window = event_loop.create_window(window_attributes_from_settings(&self.settings))
+            .map_err(GlutinWindowError::WindowCreation)?;
+        let window = Arc::new(window);
+        let frame = software::SoftwareFrame::new(window.clone())
+            .map_err(GlutinWindowError::SoftwareFallback)?;
+        self.unsupported_settings = unsupported_settings(&self.settings, &window, None);
+        self.software_frame = Some(frame);
+        self.window = Some(window);
+        Ok(())
+    }
+
+    // Drops the window surface and makes the context not current.
+    //
+    // This is called when the application is suspended,
+    // since some platforms destroy the native window surface.
+    fn release_surface(&mut self) {
+        if let Some(ctx) = &self.ctx {
+            let _ = ctx.make_not_current_in_place();
+        }
+        // The surface is already dropped when suspending a shared event loop.
+        self.surface.borrow_mut().take();
+        if self.ctx.is_some() {
+            self.events.push_back(Event::Custom(SURFACE_LOST, Arc::new(()), None));
+        }
+    }
+
+    // Recreates the window surface after being suspended,
+    // storing the error such that it is reported by `last_error`.
+    fn restore_surface(&mut self) {
+        self.surface_error = self.recreate_surface().err();
+    }
+
+    // Recreates the window surface, reusing the window and context.
+    fn recreate_surface(&mut self) -> Result<(), GlutinWindowError> {
+        use glutin::config::GetGlConfig;
+
+        if self.surface.borrow().is_some() {return Ok(())}
+        // Windows without an OpenGL context have no surface to restore.
+        let (ctx, window) = match (&self.ctx, &self.window) {
+            (Some(ctx), Some(window)) => (ctx, window),
+            _ => return Ok(()),
+        };
+        let surface = create_window_surface(&self.settings, window, &ctx.config())?;
+        ctx.make_current(&surface).map_err(GlutinWindowError::MakeCurrent)?;
+        if let Err(err) = set_vsync(&self.settings, &surface, ctx) {
+            // The context must not stay current with the surface that is dropped.
+            let _ = ctx.make_not_current_in_place();
+            return Err(err);
+        }
+        *self.surface.borrow_mut() = Some(surface);
+        self.events.push_back(Event::Custom(SURFACE_RESTORED, Arc::new(()), None));
+        Ok(())
+    }
+
+    // Resizes the window surface to match the window.
+    //
+    // Zero sizes, e.g. when the window is minimized, are skipped.
+    fn resize_surface(&self, size: PhysicalSize<u32>) {
+        use std::num::NonZeroU32;
+
+        if let (Some(ctx), Some(surface), Some(w), Some(h)) = (
+            &self.ctx,
+            &*self.surface.borrow(),
+            NonZeroU32::new(size.width),
+            NonZeroU32::new(size.height),
+        ) {
+            surface.resize(ctx, w, h);
+        }
+    }
+
+    // Handles a Winit window event.
+    fn on_window_event(&mut self, event: WindowEvent) {
+        if let WindowEvent::Resized(size) = event {
+            if self.automatic_surface_resize {
+                self.resize_surface(size);
+            }
+        }
+
+        match event {
+            WindowEvent::CloseRequested => {
+                if self.automatic_close {
+                    self.should_close = true;
+                }
+            }
+            // Rendering is driven by the application, so redraw requests are ignored.
+            // Requesting another redraw here would keep the event loop from sleeping.
+            WindowEvent::RedrawRequested => {}
+            // Controller input is disabled in the window settings.
+            WindowEvent::AxisMotion { .. } if !self.settings.get_controllers() => {}
+            event => {
+                let mut unknown = false;
+                if let Some(ev) = self.handle_event(event, &mut unknown) {
+                    if !unknown {
+                        self.events.push_back(Event::Input(ev, None));
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns the next event, pumping the event loop when the event queue is empty.
+    //
+    // Waits at most `timeout` for events, or until an event arrives when `None`.
+    fn next_event(&mut self, timeout: Option<Duration>) -> Option<Event> {
+        if self.events.is_empty() {
+            self.pump_event_loop(timeout);
+        }
+        if let Some(debug_state) = &self.debug_state {
+            debug_state.drain_events(&mut self.events);
+        }
+
+        // Get the first event in the queue
+        let event = self.events.pop_front();
+
+        // Check if we got a close event, if we did we need to mark ourselves as should-close
+        if let &Some(Event::Input(Input::Close(_), ..)) = &event {
+            self.set_should_close(true);
+        }
+
+        event
+    }
+
+    // Adds all events we got to the event queue,
+    // since winit only allows us to get all pending events at once.
+    //
+    // Does nothing after the event loop exited.
+    fn pump_event_loop(&mut self, timeout: Option<Duration>) {
+        use winit::platform::pump_events::EventLoopExtPumpEvents;
+
+        if self.exit_code.is_some() {return}
+
+        // Only wake up the event loop when polling,
+        // such that waiting sleeps until an event arrives or the timeout is reached.
+        if timeout == Some(Duration::ZERO) {
+            // Fails only when the event loop is gone, in which case pumping reports the exit.
+            let _ = self.event_loop_proxy.send_event(UserEvent::WakeUp);
+        }
+        let status = if let Some(mut event_loop) = self.event_loop.take() {
+            let status = event_loop.pump_app_events(timeout, self);
+            self.event_loop = Some(event_loop);
+            status
+        } else {
+            self.pump_shared_event_loop(timeout)
+        };
+        if let PumpStatus::Exit(code) = status {
+            self.exit_code = Some(code);
+            self.should_close = true;
+        }
+    }
+
+    // Pumps the shared event loop and handles the events of this window.
+    //
+    // Once the event loop exited, the exit status is returned without pumping,
+    // such that all windows of the event loop observe the exit.
+    fn pump_shared_event_loop(&mut self, timeout: Option<Duration>) -> PumpStatus {
+        use winit::platform::pump_events::EventLoopExtPumpEvents;
+
+        let shared = match &self.shared_event_loop {
+            Some(x) => x.inner.clone(),
+            None => return PumpStatus::Continue,
+        };
+        // Events routed here while other windows pumped are handled without waiting.
+        self.handle_routed_events(&shared);
+        let timeout = if self.events.is_empty() {timeout} else {Some(Duration::ZERO)};
+        let status = {
+            let mut shared = shared.borrow_mut();
+            let SharedEventLoop {event_loop, resumed, queues, surfaces, exit_code} = &mut *shared;
+            let status = match *exit_code {
+                Some(code) => PumpStatus::Exit(code),
+                None => {
+                    let create = if self.window.is_none() {Some(&mut *self)} else {None};
+                    event_loop.pump_app_events(timeout, &mut EventRouter {resumed, queues, surfaces, create})
+                }
+            };
+            if let PumpStatus::Exit(code) = status {
+                *exit_code = Some(code);
+            }
+            status
+        };
+        self.handle_routed_events(&shared);
+        status
+    }
+
+    // Handles the events routed to this window by a shared event loop.
+    fn handle_routed_events(&mut self, shared: &RefCell<SharedEventLoop>) {
+        let events = match self.window.as_ref()
+            .and_then(|w| shared.borrow_mut().queues.get_mut(&w.id()).map(std::mem::take))
+        {
+            Some(events) => events,
+            None => return,
+        };
+        for event in events {
+            match event {
+                RoutedEvent::Window(event) => self.on_window_event(event),
+                RoutedEvent::Custom(id, payload) =>
+                    self.events.push_back(Event::Custom(id, payload, None)),
+                RoutedEvent::Suspended => self.release_surface(),
+                RoutedEvent::Resumed => self.restore_surface(),
+            }
+        }
+    }
+
     fn fake_capture(&mut self) {
         if let Some(pos) = self.last_cursor_pos {
             // Fake capturing of cursor.
@@ -381,91 +2057,22 @@ impl GlutinWindow {
 
 impl ApplicationHandler<UserEvent> for GlutinWindow {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        use glutin::display::GetGlDisplay;
-        use glutin::config::GlConfig;
-        use glutin::context::ContextApi;
-        use glutin::context::NotCurrentGlContext;
-        use raw_window_handle::HasRawWindowHandle;
-        use std::num::NonZeroU32;
-
-        let settings = &self.settings;
-
-        let template = config_template_builder_from_settings(settings);
-        let display_builder = glutin_winit::DisplayBuilder::new();
-        let (_, gl_config) = display_builder
-            .build(event_loop, template, |configs| {
-                configs.reduce(|accum, config| {
-                    let transparency_check = config.supports_transparency().unwrap_or(false)
-                        & !accum.supports_transparency().unwrap_or(false);
-
-                    if transparency_check || config.num_samples() > accum.num_samples() {
-                        config
-                    } else {
-                        accum
-                    }
-                })
-                .unwrap()
-            }).unwrap();
-
-        let window = event_loop.create_window(winit::window::Window::default_attributes()
-            .with_inner_size(LogicalSize::<f64>::new(
-                settings.get_size().width.into(),
-                settings.get_size().height.into(),
-            ))
-            .with_title(settings.get_title())
-        ).unwrap();
-
-        let raw_window_handle = window.raw_window_handle().unwrap();
-        let draw_size = window.inner_size();
-        let dw = NonZeroU32::new(draw_size.width).unwrap();
-        let dh = NonZeroU32::new(draw_size.height).unwrap();
-        let surface_attributes = surface_attributes_builder_from_settings(settings)
-            .build(raw_window_handle, dw, dh);
-
-        let display: glutin::display::Display = gl_config.display();
-        let surface = unsafe {display.create_window_surface(&gl_config, &surface_attributes).unwrap()};
-
-        let api = graphics_api_from_settings(settings).unwrap();
-        let context_attributes = glutin::context::ContextAttributesBuilder::new()
-            .with_context_api(glutin::context::ContextApi::OpenGl(Some(glutin::context::Version::new(api.major as u8, api.minor as u8))))
-            .build(Some(raw_window_handle));
-
-        let fallback_context_attributes = glutin::context::ContextAttributesBuilder::new()
-            .with_context_api(ContextApi::Gles(None))
-            .build(Some(raw_window_handle));
-
-        let legacy_context_attributes = glutin::context::ContextAttributesBuilder::new()
-            .with_context_api(glutin::context::ContextApi::OpenGl(Some(glutin::context::Version::new(2, 1))))
-            .build(Some(raw_window_handle));
-
-        let mut not_current_gl_context = Some(unsafe {
-            if let Ok(x) = display.create_context(&gl_config, &context_attributes) {x}
-            else if let Ok(x) = display.create_context(&gl_config, &fallback_context_attributes) {x}
-            else {
-                display.create_context(&gl_config, &legacy_context_attributes).unwrap()
-            }
-        });
-
-        let ctx: glutin::context::PossiblyCurrentContext = not_current_gl_context.take().unwrap()
-            .make_current(&surface).unwrap();
-
-        if settings.get_vsync() {
-            surface.set_swap_interval(&ctx,
-                glutin::surface::SwapInterval::Wait(NonZeroU32::new(1).unwrap())).unwrap();
+        // Reuse the window and context when resuming after being suspended.
+        if self.window.is_some() {
+            self.restore_surface();
+        } else if let Err(err) = self.create_window_and_context(event_loop) {
+            self.create_error = Some(err);
         }
+    }
 
-        // Load the OpenGL function pointers.
-        gl::load_with(|s| {
-            use std::ffi::CString;
-
-            let s = CString::new(s).expect("CString::new failed");
-            display.get_proc_address(&s) as *const _
-        });
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        self.release_surface();
+    }
 
-        self.ctx = Some(ctx);
-        self.surface = Some(surface);
-        self.display = Some(display);
-        self.window = Some(Arc::new(window));
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: UserEvent) {
+        if let UserEvent::Custom {id, payload, ..} = event {
+            self.events.push_back(Event::Custom(id, payload, None));
+        }
     }
 
     fn window_event(
@@ -474,27 +2081,12 @@ impl ApplicationHandler<UserEvent> for GlutinWindow {
             _window_id: WindowId,
             event: WindowEvent,
         ) {
-            let window =  &self.get_window_ref();
-
-            match event {
-                WindowEvent::CloseRequested => {
-                    if self.automatic_close {
-                        self.should_close = true;
-                        event_loop.exit();
-                    }
-                }
-                WindowEvent::RedrawRequested => {
-                    window.request_redraw();
-                },
-                event => {
-                    let mut unknown = false;
-                    if let Some(ev) = self.handle_event(event, &mut unknown) {
-                        if !unknown {
-                            self.events.push_back(Event::Input(ev, None));
-                        }
-                    }
+            if let WindowEvent::CloseRequested = event {
+                if self.automatic_close {
+                    event_loop.exit();
                 }
             }
+            self.on_window_event(event);
         }
 }
 
@@ -511,84 +2103,29 @@ impl Window for GlutinWindow {
     fn set_should_close(&mut self, value: bool) { self.should_close = value; }
 
     fn swap_buffers(&mut self) {
-        if let (Some(ctx), Some(surface)) = (&self.ctx, &self.surface) {
+        if let (Some(ctx), Some(surface)) = (&self.ctx, &*self.surface.borrow()) {
             let _ = surface.swap_buffers(ctx);
         }
+        #[cfg(feature = "softbuffer")]
+        if let Some(frame) = &mut self.software_frame {
+            let _ = frame.present();
+        }
     }
 
     fn wait_event(&mut self) -> Event {
-        use winit::platform::pump_events::EventLoopExtPumpEvents;
         use input::{IdleArgs, Loop};
 
-        // Add all events we got to the event queue, since winit only allows us to get all pending
-        //  events at once.
-        if let Some(mut event_loop) = std::mem::replace(&mut self.event_loop, None) {
-            let event_loop_proxy = event_loop.create_proxy();
-            event_loop_proxy
-                .send_event(UserEvent::WakeUp)
-                .expect("Event loop is closed before property handling all events.");
-            event_loop.pump_app_events(None, self);
-            self.event_loop = Some(event_loop);
-        }
-
-        // Get the first event in the queue
-        let event = self.events.pop_front();
-
-        // Check if we got a close event, if we did we need to mark ourselves as should-close
-        if let &Some(Event::Input(Input::Close(_), ..)) = &event {
-            self.set_should_close(true);
-        }
-
-        event.unwrap_or(Event::Loop(Loop::Idle(IdleArgs {dt: 0.0})))
+        let start = std::time::Instant::now();
+        // Report the time spent waiting when no event was available.
+        self.next_event(None)
+            .unwrap_or_else(|| Event::Loop(Loop::Idle(IdleArgs {dt: start.elapsed().as_secs_f64()})))
     }
     fn wait_event_timeout(&mut self, timeout: Duration) -> Option<Event> {
-        use winit::platform::pump_events::EventLoopExtPumpEvents;
-
-        // Add all events we got to the event queue, since winit only allows us to get all pending
-        //  events at once.
-        if let Some(mut event_loop) = std::mem::replace(&mut self.event_loop, None) {
-            let event_loop_proxy = event_loop.create_proxy();
-            event_loop_proxy
-                .send_event(UserEvent::WakeUp)
-                .expect("Event loop is closed before property handling all events.");
-            event_loop.pump_app_events(Some(timeout), self);
-            self.event_loop = Some(event_loop);
-        }
-
-        // Get the first event in the queue
-        let event = self.events.pop_front();
-
-        // Check if we got a close event, if we did we need to mark ourselves as should-close
-        if let &Some(Event::Input(Input::Close(_), ..)) = &event {
-            self.set_should_close(true);
-        }
-
-        event
+        self.next_event(Some(timeout))
     }
     fn poll_event(&mut self) -> Option<Event> {
-        use winit::platform::pump_events::EventLoopExtPumpEvents;
-
-        // Add all events we got to the event queue, since winit only allows us to get all pending
-        //  events at once.
-        if let Some(mut event_loop) = std::mem::replace(&mut self.event_loop, None) {
-           let event_loop_proxy = event_loop.create_proxy();
-           event_loop_proxy
-               .send_event(UserEvent::WakeUp)
-               .expect("Event loop is closed before property handling all events.");
-           event_loop.pump_app_events(Some(Duration::ZERO), self);
-           self.event_loop = Some(event_loop);
-        }
-
-        // Get the first event in the queue
-        let event = self.events.pop_front();
-
-        // Check if we got a close event, if we did we need to mark ourselves as should-close
-        if let &Some(Event::Input(Input::Close(_), ..)) = &event {
-           self.set_should_close(true);
-        }
-
-        event
-     }
+        self.next_event(Some(Duration::ZERO))
+    }
 
     fn draw_size(&self) -> Size {
         let size: (f64, f64) = self.get_window_ref().inner_size().into();
@@ -669,12 +2206,38 @@ impl AdvancedWindow for GlutinWindow {
     }
 }
 
+impl raw_window_handle::HasWindowHandle for GlutinWindow {
+    /// Returns the native window handle, e.g. to create a Vulkan or wgpu surface.
+    ///
+    /// Returns `HandleError::Unavailable` when the window is not created yet.
+    fn window_handle(&self) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        match &self.window {
+            Some(window) => window.window_handle(),
+            None => Err(raw_window_handle::HandleError::Unavailable),
+        }
+    }
+}
+
+impl raw_window_handle::HasDisplayHandle for GlutinWindow {
+    /// Returns the native display handle.
+    ///
+    /// Returns `HandleError::Unavailable` when the window is not created yet.
+    fn display_handle(&self) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        match &self.window {
+            Some(window) => window.display_handle(),
+            None => Err(raw_window_handle::HandleError::Unavailable),
+        }
+    }
+}
+
+/// When the window has no OpenGL context, e.g. when a non-OpenGL graphics API was requested,
+/// `get_proc_address` returns a null pointer and `make_current` does nothing.
 impl OpenGLWindow for GlutinWindow {
     fn get_proc_address(&mut self, proc_name: &str) -> ProcAddress {
-        use std::ffi::CString;
-
-        let s = CString::new(proc_name).expect("CString::new failed");
-        self.display.as_ref().expect("No display").get_proc_address(&s) as *const _
+        match &self.display {
+            Some(display) => loader::get_proc_address(display, proc_name),
+            None => std::ptr::null(),
+        }
     }
 
     fn is_current(&self) -> bool {
@@ -684,7 +2247,7 @@ impl OpenGLWindow for GlutinWindow {
     }
 
     fn make_current(&mut self) {
-        if let (Some(ctx), Some(surface)) = (&self.ctx, &self.surface) {
+        if let (Some(ctx), Some(surface)) = (&self.ctx, &*self.surface.borrow()) {
             let _ = ctx.make_current(surface);
         }
     }
@@ -878,6 +2441,17 @@ pub fn map_mouse(mouse_button: winit::event::MouseButton) -> MouseButton {
     }
 }
 
+fn resize_input(size: PhysicalSize<u32>) -> Input {
+    Input::Resize(ResizeArgs {
+        window_size: [size.width as f64, size.height as f64],
+        draw_size: Size {
+            width: size.width as f64,
+            height: size.height as f64,
+        }
+        .into(),
+    })
+}
+
 /// Converts a winit's [`WindowEvent`] into a piston's [`Input`].
 ///
 /// For some events that will not be passed to the user, returns `None`.
@@ -899,14 +2473,7 @@ fn map_window_event(
             Some(Input::FileDrag(FileDrag::Hover(path))),
         WindowEvent::HoveredFileCancelled =>
             Some(Input::FileDrag(FileDrag::Cancel)),
-        WindowEvent::Resized(size) => Some(Input::Resize(ResizeArgs {
-            window_size: [size.width as f64, size.height as f64],
-            draw_size: Size {
-                width: size.width as f64,
-                height: size.height as f64,
-            }
-            .into(),
-        })),
+        WindowEvent::Resized(size) => Some(resize_input(size)),
         WindowEvent::CloseRequested => Some(Input::Close(CloseArgs)),
         WindowEvent::Destroyed => Some(Input::Close(CloseArgs)),
         WindowEvent::Focused(focused) => Some(Input::Focus(focused)),
@@ -991,9 +2558,133 @@ fn map_window_event(
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
 /// Custom events for the glutin event loop
 pub enum UserEvent {
     /// Do nothing, just spin the event loop
     WakeUp,
+    /// A custom event delivered to a window as `Event::Custom`.
+    ///
+    /// Use [`EventSender`] to post custom events from other threads.
+    Custom {
+        /// The window receiving the event.
+        window_id: WindowId,
+        /// The event id.
+        id: EventId,
+        /// The payload of the event.
+        payload: Arc<dyn Any + Send + Sync>,
+    },
+}
+
+impl std::fmt::Debug for UserEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UserEvent::WakeUp => write!(f, "WakeUp"),
+            UserEvent::Custom {window_id, id, ..} =>
+                write!(f, "Custom({:?}, {:?}, _)", window_id, id),
+        }
+    }
+}
+
+/// Custom events are equal when they are sent to the same window with the same id and payload.
+///
+/// Payloads are compared by identity, since they can not be compared by value.
+impl PartialEq for UserEvent {
+    fn eq(&self, other: &UserEvent) -> bool {
+        match (self, other) {
+            (UserEvent::WakeUp, UserEvent::WakeUp) => true,
+            (
+                UserEvent::Custom {window_id: a, id: a_id, payload: a_payload},
+                UserEvent::Custom {window_id: b, id: b_id, payload: b_payload},
+            ) => a == b && a_id == b_id && Arc::ptr_eq(a_payload, b_payload),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for UserEvent {}
+
+/// Posts custom events to a window from any thread.
+///
+/// Posted events wake up the event loop and are returned as `Event::Custom`
+/// by the event polling methods of the window.
+/// Use [`GlutinWindow::event_sender`] to create it.
+#[derive(Clone)]
+pub struct EventSender {
+    proxy: EventLoopProxy<UserEvent>,
+    window_id: WindowId,
+}
+
+impl EventSender {
+    /// Posts a custom event to the window.
+    ///
+    /// Returns an error when the event loop no longer exists.
+    pub fn send(
+        &self,
+        id: EventId,
+        payload: Arc<dyn Any + Send + Sync>,
+    ) -> Result<(), winit::event_loop::EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::Custom {window_id: self.window_id, id, payload})
+    }
+
+    /// Wakes up the event loop without posting an event.
+    ///
+    /// This makes a waiting `wait_event` return.
+    pub fn wake_up(&self) -> Result<(), winit::event_loop::EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(UserEvent::WakeUp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{graphics_api_from_settings, parse_gl_version, ContextAttempt, GlutinWindowError};
+
+    #[test]
+    fn test_parse_gl_version() {
+        assert_eq!(parse_gl_version("4.6.0 NVIDIA 535"), Some([4, 6]));
+        assert_eq!(parse_gl_version("OpenGL ES 3.2 Mesa 23"), Some([3, 2]));
+        assert_eq!(parse_gl_version("OpenGL ES-CM 1.1"), Some([1, 1]));
+        assert_eq!(parse_gl_version("2.1 Metal - 88"), Some([2, 1]));
+    }
+
+    #[test]
+    fn test_context_creation_error() {
+        use glutin::context::{ContextApi, Version};
+        use glutin::error::ErrorKind;
+
+        let err = GlutinWindowError::ContextCreation(vec![
+            ContextAttempt {
+                api: ContextApi::OpenGl(Some(Version::new(4, 5))),
+                error: ErrorKind::NotSupported("no core profile").into(),
+            },
+            ContextAttempt {
+                api: ContextApi::Gles(None),
+                error: ErrorKind::BadMatch.into(),
+            },
+        ]);
+        assert_eq!(
+            err.to_string(),
+            "Could not create OpenGL context: OpenGL 4.5: no core profile; \
+             OpenGL ES: arguments are inconsistent"
+        );
+    }
+
+    #[test]
+    fn test_graphics_api_from_settings() {
+        use glutin::context::{ContextApi, Version};
+        use window::{Api, WindowSettings};
+
+        let settings = WindowSettings::new("test", (640, 480));
+        let settings = settings.graphics_api(Api::opengl(4, 5));
+        assert_eq!(graphics_api_from_settings(&settings).unwrap(),
+            ContextApi::OpenGl(Some(Version::new(4, 5))));
+
+        let settings = settings.graphics_api(Api {api: "OpenGL ES".into(), major: 3, minor: 0});
+        assert_eq!(graphics_api_from_settings(&settings).unwrap(),
+            ContextApi::Gles(Some(Version::new(3, 0))));
+
+        let settings = settings.graphics_api(Api {api: "Vulkan".into(), major: 1, minor: 0});
+        let err = graphics_api_from_settings(&settings).unwrap_err();
+        assert_eq!(err.found, "Vulkan");
+        assert_eq!(err.expected, ["OpenGL", "OpenGL ES"]);
+    }
 }
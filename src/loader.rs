@@ -0,0 +1,66 @@
+//! Loading OpenGL functions of a specific display.
+
+use glutin::display::{Display, GlDisplay};
+use window::ProcAddress;
+
+/// Loads OpenGL functions from the display of a specific context.
+///
+/// Unlike `gl::load_with`, which overwrites global function pointers,
+/// the loader keeps track of which display the functions come from.
+/// Use this when there are several windows or contexts.
+///
+/// Some platforms, e.g. WGL, return functions that are only valid for the current context.
+/// Load the functions while the context they are used with is current.
+#[derive(Clone, Debug)]
+pub struct GlLoader {
+    display: Display,
+}
+
+impl GlLoader {
+    /// Creates a new loader for a display.
+    pub fn new(display: Display) -> GlLoader {
+        GlLoader {display}
+    }
+
+    /// Returns the display of the loader.
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+
+    /// Returns the address of an OpenGL function.
+    pub fn get_proc_address(&self, proc_name: &str) -> ProcAddress {
+        get_proc_address(&self.display, proc_name)
+    }
+
+    /// Loads the global function pointers of the `gl` crate.
+    pub fn load_global(&self) {
+        gl::load_with(|s| self.get_proc_address(s) as *const _);
+    }
+
+    /// Creates a `glow` context.
+    ///
+    /// # Safety
+    ///
+    /// The OpenGL context must be current while the `glow` context is in use.
+    #[cfg(feature = "glow")]
+    pub unsafe fn glow_context(&self) -> glow::Context {
+        glow::Context::from_loader_function_cstr(|s| self.display.get_proc_address(s))
+    }
+
+    /// Loads OpenGL functions into a struct.
+    ///
+    /// The struct keeps its own function pointers,
+    /// such that several contexts can be used side by side.
+    #[cfg(feature = "gl_struct")]
+    pub fn gl(&self) -> crate::gl_struct::Gl {
+        crate::gl_struct::Gl::load_with(|s| self.get_proc_address(s) as *const _)
+    }
+}
+
+// Returns the address of an OpenGL function of a display.
+pub(crate) fn get_proc_address(display: &Display, proc_name: &str) -> ProcAddress {
+    use std::ffi::CString;
+
+    let s = CString::new(proc_name).expect("CString::new failed");
+    display.get_proc_address(&s) as *const _
+}
@@ -0,0 +1,129 @@
+//! OpenGL contexts sharing objects with a window context.
+
+use glutin::config::{ConfigSurfaceTypes, GetGlConfig, GlConfig};
+use glutin::context::{
+    GlContext,
+    NotCurrentContext,
+    NotCurrentGlContext,
+    PossiblyCurrentContext,
+    PossiblyCurrentGlContext,
+};
+use glutin::display::{GetGlDisplay, GlDisplay};
+use glutin::surface::{PbufferSurface, Surface};
+use window::ProcAddress;
+
+use std::num::NonZeroU32;
+
+use super::{create_context, loader, ContextFlags, GlLoader, GlutinWindowError};
+
+/// An OpenGL context that shares objects with the context of a window.
+///
+/// Textures, buffers and other objects created by one context
+/// can be used by the other, e.g. to load resources on a worker thread.
+/// Objects that are containers, such as vertex array objects and framebuffers, are not shared.
+///
+/// This handle can be sent to another thread, where it is made current.
+/// Use [`crate::GlutinWindow::create_shared_context`] to create it.
+pub struct SharedContext {
+    ctx: NotCurrentContext,
+    surface: Option<Surface<PbufferSurface>>,
+}
+
+impl SharedContext {
+    // Creates a context sharing objects with `main_ctx`.
+    //
+    // Uses the same config, graphics API and flags as the main context,
+    // which is required by some platforms for sharing.
+    pub(crate) fn new(
+        main_ctx: &PossiblyCurrentContext,
+        flags: &ContextFlags,
+    ) -> Result<SharedContext, GlutinWindowError> {
+        let display = main_ctx.display();
+        let gl_config = main_ctx.config();
+        let ctx = create_context(
+            &display,
+            &gl_config,
+            None,
+            std::iter::once(main_ctx.context_api()),
+            flags,
+            Some(main_ctx),
+        )?;
+
+        // Prefer a tiny pbuffer and fall back to surfaceless.
+        let surface = if gl_config.config_surface_types().contains(ConfigSurfaceTypes::PBUFFER) {
+            let surface_attributes = glutin::surface::SurfaceAttributesBuilder::<PbufferSurface>::new()
+                .build(NonZeroU32::MIN, NonZeroU32::MIN);
+            Some(unsafe {display.create_pbuffer_surface(&gl_config, &surface_attributes)}
+                .map_err(GlutinWindowError::SurfaceCreation)?)
+        } else if supports_surfaceless(&ctx) {
+            None
+        } else {
+            return Err(GlutinWindowError::SurfaceCreation(glutin::error::ErrorKind::NotSupported(
+                "the config supports neither pbuffers nor surfaceless contexts"
+            ).into()));
+        };
+        Ok(SharedContext {ctx, surface})
+    }
+
+    /// Makes the context current on the calling thread.
+    pub fn make_current(self) -> Result<CurrentSharedContext, glutin::error::Error> {
+        let SharedContext {ctx, surface} = self;
+        let ctx = match &surface {
+            Some(surface) => ctx.make_current(surface)?,
+            None => make_current_surfaceless(ctx)?,
+        };
+        Ok(CurrentSharedContext {ctx, surface})
+    }
+}
+
+/// A shared context that is current on the calling thread.
+///
+/// Load OpenGL functions with [`CurrentSharedContext::get_proc_address`].
+/// Call `glFinish` or use a fence before the main context uses the created objects.
+pub struct CurrentSharedContext {
+    /// The OpenGL context.
+    pub ctx: PossiblyCurrentContext,
+    surface: Option<Surface<PbufferSurface>>,
+}
+
+impl CurrentSharedContext {
+    /// Returns the address of an OpenGL function.
+    pub fn get_proc_address(&self, proc_name: &str) -> ProcAddress {
+        loader::get_proc_address(&self.ctx.display(), proc_name)
+    }
+
+    /// Returns a loader of OpenGL functions for this context.
+    pub fn loader(&self) -> GlLoader {
+        GlLoader::new(self.ctx.display())
+    }
+
+    /// Makes the context not current, such that it can be sent to another thread.
+    pub fn make_not_current(self) -> Result<SharedContext, glutin::error::Error> {
+        let CurrentSharedContext {ctx, surface} = self;
+        Ok(SharedContext {ctx: ctx.make_not_current()?, surface})
+    }
+}
+
+// Returns `true` when the context can be made current without a surface.
+fn supports_surfaceless(ctx: &NotCurrentContext) -> bool {
+    match ctx {
+        #[cfg(all(any(windows, unix), not(target_os = "macos"), not(target_os = "ios")))]
+        NotCurrentContext::Egl(_) => true,
+        #[allow(unreachable_patterns)]
+        _ => false,
+    }
+}
+
+// Makes the context current without a surface.
+fn make_current_surfaceless(
+    ctx: NotCurrentContext
+) -> Result<PossiblyCurrentContext, glutin::error::Error> {
+    match ctx {
+        #[cfg(all(any(windows, unix), not(target_os = "macos"), not(target_os = "ios")))]
+        NotCurrentContext::Egl(ctx) => Ok(PossiblyCurrentContext::Egl(ctx.make_current_surfaceless()?)),
+        #[allow(unreachable_patterns)]
+        _ => Err(glutin::error::ErrorKind::NotSupported(
+            "surfaceless contexts are not supported by the platform"
+        ).into()),
+    }
+}
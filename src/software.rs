@@ -0,0 +1,47 @@
+//! CPU framebuffer presented with `softbuffer` when OpenGL is not available.
+
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+type WinitWindow = Arc<winit::window::Window>;
+
+// A pixel buffer that is drawn on the CPU and presented to the window.
+pub(crate) struct SoftwareFrame {
+    surface: softbuffer::Surface<WinitWindow, WinitWindow>,
+    // The pixels of the frame, which is kept between presents.
+    pixels: Vec<u32>,
+    // The size of the frame in pixels.
+    size: [u32; 2],
+}
+
+impl SoftwareFrame {
+    pub(crate) fn new(window: WinitWindow) -> Result<SoftwareFrame, softbuffer::SoftBufferError> {
+        let context = softbuffer::Context::new(window.clone())?;
+        let surface = softbuffer::Surface::new(&context, window)?;
+        Ok(SoftwareFrame {surface, pixels: vec![], size: [0, 0]})
+    }
+
+    // Returns the pixels of the frame, resized to the size of the window.
+    pub(crate) fn pixels_mut(&mut self) -> &mut [u32] {
+        let size = self.surface.window().inner_size();
+        let size = [size.width, size.height];
+        if size != self.size {
+            self.size = size;
+            self.pixels.resize(size[0] as usize * size[1] as usize, 0);
+        }
+        &mut self.pixels
+    }
+
+    // Copies the frame to the window.
+    pub(crate) fn present(&mut self) -> Result<(), softbuffer::SoftBufferError> {
+        // Skip presenting while the window is minimized.
+        let (w, h) = match (NonZeroU32::new(self.size[0]), NonZeroU32::new(self.size[1])) {
+            (Some(w), Some(h)) => (w, h),
+            _ => return Ok(()),
+        };
+        self.surface.resize(w, h)?;
+        let mut buffer = self.surface.buffer_mut()?;
+        buffer.copy_from_slice(&self.pixels);
+        buffer.present()
+    }
+}